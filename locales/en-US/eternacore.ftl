@@ -0,0 +1,62 @@
+# Messages shown by the framework and built-in commands.
+# Copy this file into a directory named after another Discord locale (e.g. `locales/de`) to translate them.
+
+ping-response = Pong!
+
+purge-nothing-left = Nothing left to delete.
+purge-deleted = { $count ->
+    [one] { $count } message deleted successfully!
+   *[other] { $count } messages deleted successfully!
+}
+
+help-title = Commands
+help-empty = There are no commands you can use here.
+help-uncategorized = General
+help-page = Page { $page } of { $pages }
+help-previous = Previous
+help-next = Next
+help-unknown = There's no command named `{ $name }`.
+help-usage = Usage
+help-subcommands = Subcommands
+help-aliases = Aliases
+help-category = Category
+help-permissions = Required permissions
+help-cooldown = Cooldown
+help-cooldown-value = { $uses ->
+    [one] Once
+   *[other] { $uses } times
+} every { $seconds } seconds
+help-context-user = Right-click a user and pick it from Apps
+help-context-message = Right-click a message and pick it from Apps
+
+error-missing-permissions = You need { $permissions } permissions to do that.
+error-missing-bot-permissions = I don't have enough permissions to do that, please, enable { $permissions } permissions and execute the command again.
+error-disabled = This command is currently disabled.
+error-guild-only = This command can only be used in servers.
+error-dm-only = This command can only be used in direct messages.
+error-owner-only = This command can only be used by owners of the bot.
+error-nsfw-only = This command can only be used in age-restricted channels.
+error-cooldown = This command is on cooldown, try again in { $seconds }s.
+error-internal = Something went wrong while executing the command, please, try again later.
+
+argument-missing = Missing required argument `{ $name }`.
+argument-mistyped = Argument `{ $name }` must be a { $expected }.
+argument-invalid = `{ $value }` is not a valid { $kind } for argument `{ $name }`.
+argument-out-of-range = `{ $value }` is out of range for argument `{ $name }`.
+argument-invalid-length = Argument `{ $name }` must be { $min } to { $max } characters long.
+argument-not-a-choice = `{ $value }` isn't one of the choices for argument `{ $name }`: `{ $choices }`.
+argument-missing-subcommand = Expected one of: `{ $expected }`.
+argument-unknown-subcommand = Unknown subcommand `{ $name }`.
+argument-trailing = Unexpected arguments: `{ $input }`.
+
+option-kind-string = text
+option-kind-integer = integer
+option-kind-number = number
+option-kind-boolean = boolean
+option-kind-user = user
+option-kind-member = server member
+option-kind-role = role
+option-kind-channel = channel
+option-kind-mentionable = user or role
+option-kind-attachment = attachment
+option-kind-value = value
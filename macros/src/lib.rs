@@ -0,0 +1,671 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    meta::ParseNestedMeta, parse_macro_input, spanned::Spanned, Attribute, Error, Expr, ExprArray,
+    ExprLit, ExprUnary, FnArg, GenericArgument, ItemFn, Lit, LitInt, LitStr, Pat, PathArguments,
+    Result, Type,
+};
+
+/// Turns an asynchronous function into a unit structure implementing `Command`.
+///
+/// The first two arguments of the function receive `Request` and `Response`,
+/// every following argument is an option of the command and has to be annotated with `#[option]`.
+/// Wrapping the type in `Option` makes the option optional. The function returns `CommandResult`.
+///
+/// ```ignore
+/// /// Bulk delete up to 100 messages
+/// #[command]
+/// async fn purge(
+///     req: Request,
+///     res: Response,
+///     #[option(description = "Number of messages to delete", min = 0, max = 100)] number: i64,
+/// ) -> CommandResult {
+///     // ...
+/// }
+/// ```
+///
+/// Command attributes:
+///  - `name` - name of the command, defaults to the name of the function
+///  - `description` - description of the command, defaults to the doc comment
+///  - `aliases` - array of other names the command can be invoked by as a message command
+///  - `autocomplete` - asynchronous function receiving `Request`, focused option name and its value,
+///    which returns `Vec<Choice>`
+///  - `user_permissions(...)`/`bot_permissions(...)` - names of `Permissions` the user or the bot needs,
+///    e.g. `user_permissions(MANAGE_MESSAGES)`
+///  - `guild_only`/`dm_only`/`owner_only`/`nsfw_only` - restrict where and by whom the command can be used
+///  - `context_menu(user)`/`context_menu(message)` - register the command in the context menu of users
+///    or messages instead of as a slash command, such commands can't take options
+///  - `localized_names`/`localized_descriptions` - arrays of `("locale", "text")` pairs
+///    translating the name or description of the command, e.g. `localized_names = [("de", "löschen")]`
+///  - `guilds` - array of IDs of guilds the command is registered in instead of globally
+///  - `check` - asynchronous function receiving `&Request` which returns `CommandResult`,
+///    can be specified multiple times
+///  - `cooldown(scope, uses = ..., seconds = ...)` - allow `uses` uses per `seconds`, counted per `user`,
+///    `member`, `channel` or `guild`. Uses are reset all at once unless `token_bucket` is specified
+///  - `slow` - defer the response before running the command
+///  - `ephemeral` - make responses deferred by the framework ephemeral
+///
+/// Option attributes:
+///  - `description` - description of the option (required)
+///  - `name` - name of the option, defaults to the name of the argument
+///  - `min`/`max` - bounds of integer and number options
+///  - `min_length`/`max_length` - bounds of the length of text options
+///  - `choices` - array of values the user picks from
+///  - `autocomplete` - suggest values through the command's `autocomplete` function
+///  - `localized_names`/`localized_descriptions` - arrays of `("locale", "text")` pairs
+///    translating the name or description of the option
+#[proc_macro_attribute]
+pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = CommandArgs::default();
+    let parser = syn::meta::parser(|meta| args.parse(meta));
+    parse_macro_input!(attr with parser);
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand_command(args, function)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct CommandArgs {
+    name: Option<LitStr>,
+    description: Option<LitStr>,
+    aliases: Vec<LitStr>,
+    autocomplete: Option<syn::Path>,
+    user_permissions: Vec<syn::Ident>,
+    bot_permissions: Vec<syn::Ident>,
+    guild_only: bool,
+    dm_only: bool,
+    owner_only: bool,
+    nsfw_only: bool,
+    context_menu: Option<syn::Ident>,
+    localized_names: Vec<(LitStr, LitStr)>,
+    localized_descriptions: Vec<(LitStr, LitStr)>,
+    guilds: Vec<LitInt>,
+    checks: Vec<syn::Path>,
+    cooldown: Option<CooldownArgs>,
+    slow: bool,
+    ephemeral: bool,
+}
+impl CommandArgs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("description") {
+            self.description = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("aliases") {
+            let aliases: ExprArray = meta.value()?.parse()?;
+            for alias in aliases.elems {
+                match alias {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(alias),
+                        ..
+                    }) => self.aliases.push(alias),
+                    alias => return Err(Error::new(alias.span(), "expected a string")),
+                }
+            }
+        } else if meta.path.is_ident("autocomplete") {
+            self.autocomplete = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("user_permissions") {
+            meta.parse_nested_meta(|permission| {
+                self.user_permissions
+                    .push(permission.path.require_ident()?.clone());
+                Ok(())
+            })?;
+        } else if meta.path.is_ident("bot_permissions") {
+            meta.parse_nested_meta(|permission| {
+                self.bot_permissions
+                    .push(permission.path.require_ident()?.clone());
+                Ok(())
+            })?;
+        } else if meta.path.is_ident("guild_only") {
+            self.guild_only = true;
+        } else if meta.path.is_ident("dm_only") {
+            self.dm_only = true;
+        } else if meta.path.is_ident("owner_only") {
+            self.owner_only = true;
+        } else if meta.path.is_ident("nsfw_only") {
+            self.nsfw_only = true;
+        } else if meta.path.is_ident("context_menu") {
+            meta.parse_nested_meta(|kind| {
+                let kind = match kind.path.require_ident()?.to_string().as_str() {
+                    "user" => "User",
+                    "message" => "Message",
+                    _ => return Err(kind.error("expected `user` or `message`")),
+                };
+                self.context_menu = Some(format_ident!("{}", kind));
+                Ok(())
+            })?;
+        } else if meta.path.is_ident("localized_names") {
+            self.localized_names = parse_localizations(&meta)?;
+        } else if meta.path.is_ident("localized_descriptions") {
+            self.localized_descriptions = parse_localizations(&meta)?;
+        } else if meta.path.is_ident("guilds") {
+            let guilds: ExprArray = meta.value()?.parse()?;
+            for guild in guilds.elems {
+                match guild {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Int(guild),
+                        ..
+                    }) => self.guilds.push(guild),
+                    guild => return Err(Error::new(guild.span(), "expected a guild ID")),
+                }
+            }
+        } else if meta.path.is_ident("check") {
+            self.checks.push(meta.value()?.parse()?);
+        } else if meta.path.is_ident("cooldown") {
+            let mut cooldown = CooldownArgs::default();
+            meta.parse_nested_meta(|meta| cooldown.parse(meta))?;
+            if cooldown.scope.is_none() || cooldown.uses.is_none() || cooldown.seconds.is_none() {
+                return Err(meta.error("cooldown requires a scope, `uses` and `seconds`"));
+            }
+            self.cooldown = Some(cooldown);
+        } else if meta.path.is_ident("slow") {
+            self.slow = true;
+        } else if meta.path.is_ident("ephemeral") {
+            self.ephemeral = true;
+        } else {
+            return Err(meta.error("unknown command attribute"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct CooldownArgs {
+    scope: Option<syn::Ident>,
+    uses: Option<LitInt>,
+    seconds: Option<LitInt>,
+    token_bucket: bool,
+}
+impl CooldownArgs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("uses") {
+            let uses: LitInt = meta.value()?.parse()?;
+            if uses.base10_parse::<u32>()? == 0 {
+                return Err(syn::Error::new(
+                    uses.span(),
+                    "cooldown must allow at least one use",
+                ));
+            }
+            self.uses = Some(uses);
+        } else if meta.path.is_ident("seconds") {
+            self.seconds = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("token_bucket") {
+            self.token_bucket = true;
+        } else {
+            let scope = match meta.path.require_ident()?.to_string().as_str() {
+                "user" => "User",
+                "member" => "Member",
+                "channel" => "Channel",
+                "guild" => "Guild",
+                _ => return Err(meta.error("unknown cooldown attribute")),
+            };
+            self.scope = Some(format_ident!("{}", scope));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct OptionArgs {
+    name: Option<LitStr>,
+    description: Option<LitStr>,
+    min: Option<Expr>,
+    max: Option<Expr>,
+    min_length: Option<LitInt>,
+    max_length: Option<LitInt>,
+    choices: Vec<Expr>,
+    autocomplete: bool,
+    localized_names: Vec<(LitStr, LitStr)>,
+    localized_descriptions: Vec<(LitStr, LitStr)>,
+}
+impl OptionArgs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("description") {
+            self.description = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("min") {
+            self.min = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("max") {
+            self.max = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("min_length") {
+            self.min_length = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("max_length") {
+            self.max_length = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("choices") {
+            let choices: ExprArray = meta.value()?.parse()?;
+            self.choices = choices.elems.into_iter().collect();
+        } else if meta.path.is_ident("autocomplete") {
+            self.autocomplete = true;
+        } else if meta.path.is_ident("localized_names") {
+            self.localized_names = parse_localizations(&meta)?;
+        } else if meta.path.is_ident("localized_descriptions") {
+            self.localized_descriptions = parse_localizations(&meta)?;
+        } else {
+            return Err(meta.error("unknown option attribute"));
+        }
+        Ok(())
+    }
+}
+
+/// Option declared by a function argument.
+struct CommandOption {
+    ident: syn::Ident,
+    ty: Type,
+    required: bool,
+    args: OptionArgs,
+}
+
+fn expand_command(args: CommandArgs, mut function: ItemFn) -> Result<TokenStream2> {
+    if function.sig.asyncness.is_none() {
+        return Err(Error::new(
+            function.sig.fn_token.span(),
+            "commands must be asynchronous",
+        ));
+    }
+    if function.sig.inputs.len() < 2 {
+        return Err(Error::new(
+            function.sig.inputs.span(),
+            "commands must accept `Request` and `Response` as the first two arguments",
+        ));
+    }
+
+    let fn_ident = function.sig.ident.clone();
+    let name = args
+        .name
+        .map(|name| name.value())
+        .unwrap_or_else(|| fn_ident.to_string());
+    let description = match args.description {
+        Some(description) => description.value(),
+        None => doc_comment(&function.attrs),
+    };
+    let struct_ident = format_ident!("{}", to_camel_case(&fn_ident.to_string()));
+    let vis = function.vis.clone();
+    let aliases = &args.aliases;
+    let user_permissions = &args.user_permissions;
+    let bot_permissions = &args.bot_permissions;
+    let guild_only = args.guild_only;
+    let dm_only = args.dm_only;
+    let owner_only = args.owner_only;
+    let nsfw_only = args.nsfw_only;
+    let guilds = &args.guilds;
+    let (name_locales, names): (Vec<_>, Vec<_>) = args.localized_names.iter().cloned().unzip();
+    let (description_locales, descriptions): (Vec<_>, Vec<_>) =
+        args.localized_descriptions.iter().cloned().unzip();
+    let slow = args.slow;
+    let ephemeral = args.ephemeral;
+
+    let mut options = Vec::new();
+    for input in function.sig.inputs.iter_mut().skip(2) {
+        let input = match input {
+            FnArg::Typed(input) => input,
+            FnArg::Receiver(receiver) => {
+                return Err(Error::new(receiver.span(), "commands can't take `self`"))
+            }
+        };
+        let ident = match &*input.pat {
+            Pat::Ident(pat) => pat.ident.clone(),
+            pat => return Err(Error::new(pat.span(), "expected an argument name")),
+        };
+
+        let mut option_args = None;
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("option"))
+        {
+            let mut args = OptionArgs::default();
+            attr.parse_nested_meta(|meta| args.parse(meta))?;
+            option_args = Some(args);
+        }
+        input.attrs.retain(|attr| !attr.path().is_ident("option"));
+        let option_args = match option_args {
+            Some(args) if args.description.is_some() => args,
+            _ => {
+                return Err(Error::new(
+                    input.span(),
+                    "options must be annotated with `#[option(description = \"...\")]`",
+                ))
+            }
+        };
+
+        let (ty, required) = match optional_inner(&input.ty) {
+            Some(inner) => (inner.clone(), false),
+            None => ((*input.ty).clone(), true),
+        };
+        options.push(CommandOption {
+            ident,
+            ty,
+            required,
+            args: option_args,
+        });
+    }
+
+    if let (Some(kind), Some(option)) = (&args.context_menu, options.first()) {
+        return Err(Error::new(
+            option.ident.span(),
+            format!(
+                "{} context menu commands can't take options",
+                kind.to_string().to_lowercase()
+            ),
+        ));
+    }
+    let kind = args.context_menu.map(|kind| {
+        quote! {
+            fn kind(&self) -> ::serenity::model::application::command::CommandType {
+                ::serenity::model::application::command::CommandType::#kind
+            }
+        }
+    });
+
+    let layouts = options.iter().map(option_layout);
+    let extractions = options.iter().map(|option| {
+        let ident = &option.ident;
+        let ty = &option.ty;
+        let name = option_name(option);
+        let getter = if option.required {
+            quote!(get)
+        } else {
+            quote!(get_optional)
+        };
+        quote! {
+            let #ident = __command.#getter::<#ty>(#name)?;
+        }
+    });
+    let idents = options.iter().map(|option| &option.ident);
+    let check = (!args.checks.is_empty()).then(|| {
+        let checks = &args.checks;
+        quote! {
+            async fn check(
+                &self,
+                req: &::eternacore::commands::framework::Request,
+            ) -> ::eternacore::commands::framework::CommandResult {
+                #(#checks(req).await?;)*
+                Ok(())
+            }
+        }
+    });
+    let cooldown = args.cooldown.map(|cooldown| {
+        let scope = cooldown.scope;
+        let uses = cooldown.uses;
+        let seconds = cooldown.seconds;
+        let constructor = if cooldown.token_bucket {
+            quote!(token_bucket)
+        } else {
+            quote!(fixed_window)
+        };
+        quote! {
+            fn cooldown(&self) -> Option<::eternacore::commands::framework::Cooldown> {
+                Some(::eternacore::commands::framework::Cooldown::#constructor(
+                    ::eternacore::commands::framework::cooldown::Scope::#scope,
+                    #uses,
+                    ::std::time::Duration::from_secs(#seconds),
+                ))
+            }
+        }
+    });
+    let autocomplete = args.autocomplete.map(|autocomplete| {
+        quote! {
+            async fn autocomplete(
+                &self,
+                req: ::eternacore::commands::framework::Request,
+                option: &str,
+                value: &str,
+            ) -> Vec<::eternacore::commands::framework::Choice> {
+                #autocomplete(req, option, value).await
+            }
+        }
+    });
+
+    Ok(quote! {
+        #function
+
+        #[doc = #description]
+        #vis struct #struct_ident;
+
+        #[::serenity::async_trait]
+        impl ::eternacore::commands::framework::Command for #struct_ident {
+            fn name<'s>(&self) -> &'s str {
+                #name
+            }
+            fn description<'s>(&self) -> &'s str {
+                #description
+            }
+            fn aliases<'s>(&self) -> Vec<&'s str> {
+                vec![#(#aliases),*]
+            }
+            fn localized_names<'s>(&self) -> Vec<(&'s str, &'s str)> {
+                vec![#((#name_locales, #names)),*]
+            }
+            fn localized_descriptions<'s>(&self) -> Vec<(&'s str, &'s str)> {
+                vec![#((#description_locales, #descriptions)),*]
+            }
+
+            fn layout<'s>(
+                &self,
+                command: &'s mut ::serenity::builder::CreateApplicationCommand,
+            ) -> &'s mut ::serenity::builder::CreateApplicationCommand {
+                self.default_layout(command)
+                    #(#layouts)*
+            }
+
+            fn user_permissions(&self) -> ::serenity::model::Permissions {
+                ::serenity::model::Permissions::empty()
+                    #(| ::serenity::model::Permissions::#user_permissions)*
+            }
+            fn bot_permissions(&self) -> ::serenity::model::Permissions {
+                ::serenity::model::Permissions::empty()
+                    #(| ::serenity::model::Permissions::#bot_permissions)*
+            }
+            fn guild_only(&self) -> bool {
+                #guild_only
+            }
+            fn dm_only(&self) -> bool {
+                #dm_only
+            }
+            fn owner_only(&self) -> bool {
+                #owner_only
+            }
+            fn nsfw_only(&self) -> bool {
+                #nsfw_only
+            }
+            #kind
+            fn guilds(&self) -> Vec<::serenity::model::id::GuildId> {
+                vec![#(::serenity::model::id::GuildId(#guilds)),*]
+            }
+            #check
+            #cooldown
+
+            fn slow(&self) -> bool {
+                #slow
+            }
+            fn ephemeral(&self) -> bool {
+                #ephemeral
+            }
+
+            async fn run(
+                &self,
+                req: ::eternacore::commands::framework::Request,
+                res: ::eternacore::commands::framework::Response,
+            ) -> ::eternacore::commands::framework::CommandResult {
+                let __command = req.command();
+                #(#extractions)*
+                #fn_ident(req, res, #(#idents),*).await
+            }
+
+            #autocomplete
+        }
+    })
+}
+
+fn option_name(option: &CommandOption) -> String {
+    option
+        .args
+        .name
+        .as_ref()
+        .map(|name| name.value())
+        .unwrap_or_else(|| option.ident.to_string())
+}
+
+fn option_layout(option: &CommandOption) -> TokenStream2 {
+    let ty = &option.ty;
+    let name = option_name(option);
+    let description = &option.args.description;
+    let required = option.required;
+
+    let mut extras = Vec::new();
+    if let Some(min) = &option.args.min {
+        extras.push(if is_float(min) {
+            quote!(.min_number_value(#min))
+        } else {
+            quote!(.min_int_value(#min))
+        });
+    }
+    if let Some(max) = &option.args.max {
+        extras.push(if is_float(max) {
+            quote!(.max_number_value(#max))
+        } else {
+            quote!(.max_int_value(#max))
+        });
+    }
+    if option.args.autocomplete {
+        extras.push(quote!(.set_autocomplete(true)));
+    }
+    if let Some(min_length) = &option.args.min_length {
+        extras.push(quote!(.min_length(#min_length)));
+    }
+    if let Some(max_length) = &option.args.max_length {
+        extras.push(quote!(.max_length(#max_length)));
+    }
+    for (locale, name) in &option.args.localized_names {
+        extras.push(quote!(.name_localized(#locale, #name)));
+    }
+    for (locale, description) in &option.args.localized_descriptions {
+        extras.push(quote!(.description_localized(#locale, #description)));
+    }
+    for choice in &option.args.choices {
+        extras.push(match choice {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(string),
+                ..
+            }) => quote!(.add_string_choice(#string, #string)),
+            choice if is_float(choice) => {
+                quote!(.add_number_choice(stringify!(#choice), #choice))
+            }
+            choice => quote!(.add_int_choice(stringify!(#choice), #choice)),
+        });
+    }
+
+    quote! {
+        .create_option(|option| {
+            option
+                .name(#name)
+                .description(#description)
+                .kind(<#ty as ::eternacore::commands::framework::FromOption>::KIND)
+                .required(#required)
+                #(#extras)*
+        })
+    }
+}
+
+/// Parses an array of `("locale", "text")` pairs.
+fn parse_localizations(meta: &ParseNestedMeta) -> Result<Vec<(LitStr, LitStr)>> {
+    let pairs: ExprArray = meta.value()?.parse()?;
+    let mut localizations = Vec::new();
+    for pair in pairs.elems {
+        let elems: Vec<&Expr> = match &pair {
+            Expr::Tuple(tuple) => tuple.elems.iter().collect(),
+            _ => Vec::new(),
+        };
+        match elems.as_slice() {
+            [Expr::Lit(ExprLit {
+                lit: Lit::Str(locale),
+                ..
+            }), Expr::Lit(ExprLit {
+                lit: Lit::Str(text),
+                ..
+            })] => localizations.push((locale.clone(), text.clone())),
+            _ => {
+                return Err(Error::new(
+                    pair.span(),
+                    "expected a `(\"locale\", \"text\")` pair",
+                ))
+            }
+        }
+    }
+    Ok(localizations)
+}
+
+/// Returns `T` if the type is written as `Option<T>`.
+fn optional_inner(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => match arguments.args.first()? {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_float(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(_), ..
+        }) => true,
+        Expr::Unary(ExprUnary { expr, .. }) => is_float(expr),
+        _ => false,
+    }
+}
+
+fn doc_comment(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta.require_name_value().ok()?.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(doc), ..
+            }) => Some(doc.value().trim().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn to_camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,115 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::fmt;
+
+use fluent_bundle::FluentArgs;
+use serenity::model::{
+    application::command::CommandOptionType,
+    channel::{Attachment, PartialChannel},
+    guild::{PartialMember, Role},
+    id::{ChannelId, RoleId, UserId},
+    prelude::interaction::application_command::CommandDataOptionValue,
+    user::User,
+};
+
+use super::Catalog;
+
+/// Reasons why an option can't be extracted from a command.
+#[derive(Debug, Clone)]
+pub enum ArgumentError {
+    /// Option with this name wasn't given.
+    Missing(String),
+    /// Option was given, but it holds a value of another type.
+    Mistyped {
+        name: String,
+        /// Catalog key of the name of the expected type, see [FromOption::EXPECTED].
+        expected: &'static str,
+    },
+}
+impl ArgumentError {
+    /// Renders the explanation shown to the user, `render` looks up messages in a catalog.
+    pub fn render(&self, render: &dyn Fn(&str, Option<&FluentArgs>) -> String) -> String {
+        let mut args = FluentArgs::new();
+        match self {
+            ArgumentError::Missing(name) => {
+                args.set("name", name.clone());
+                render("argument-missing", Some(&args))
+            }
+            ArgumentError::Mistyped { name, expected } => {
+                args.set("name", name.clone());
+                args.set("expected", render(expected, None));
+                render("argument-mistyped", Some(&args))
+            }
+        }
+    }
+}
+impl fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&|key, args| Catalog::built_in().render(None, key, args)))
+    }
+}
+impl std::error::Error for ArgumentError {}
+
+/// Type which can be extracted from a resolved command option.
+pub trait FromOption: Sized {
+    /// Catalog key of the human readable name of the type, used in error messages.
+    const EXPECTED: &'static str;
+    /// Type of the option this value is registered as.
+    const KIND: CommandOptionType;
+
+    fn from_option(value: &CommandDataOptionValue) -> Option<Self>;
+}
+
+macro_rules! from_option {
+    ($type:ty, $expected:literal, $kind:ident, $pattern:pat => $value:expr) => {
+        impl FromOption for $type {
+            const EXPECTED: &'static str = $expected;
+            const KIND: CommandOptionType = CommandOptionType::$kind;
+
+            fn from_option(value: &CommandDataOptionValue) -> Option<Self> {
+                match value {
+                    $pattern => Some($value),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+from_option!(String, "option-kind-string", String, CommandDataOptionValue::String(string) => string.clone());
+from_option!(i64, "option-kind-integer", Integer, CommandDataOptionValue::Integer(int) => *int);
+from_option!(f64, "option-kind-number", Number, CommandDataOptionValue::Number(number) => *number);
+from_option!(bool, "option-kind-boolean", Boolean, CommandDataOptionValue::Boolean(boolean) => *boolean);
+from_option!(User, "option-kind-user", User, CommandDataOptionValue::User(user, _) => user.clone());
+from_option!(UserId, "option-kind-user", User, CommandDataOptionValue::User(user, _) => user.id);
+from_option!(Role, "option-kind-role", Role, CommandDataOptionValue::Role(role) => role.clone());
+from_option!(RoleId, "option-kind-role", Role, CommandDataOptionValue::Role(role) => role.id);
+from_option!(PartialChannel, "option-kind-channel", Channel, CommandDataOptionValue::Channel(channel) => channel.clone());
+from_option!(ChannelId, "option-kind-channel", Channel, CommandDataOptionValue::Channel(channel) => channel.id);
+from_option!(Attachment, "option-kind-attachment", Attachment, CommandDataOptionValue::Attachment(attachment) => attachment.clone());
+
+impl FromOption for PartialMember {
+    const EXPECTED: &'static str = "option-kind-member";
+    const KIND: CommandOptionType = CommandOptionType::User;
+
+    fn from_option(value: &CommandDataOptionValue) -> Option<Self> {
+        match value {
+            CommandDataOptionValue::User(_, member) => member.clone(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use serenity::builder::CreateAutocompleteResponse;
+
+/// Maximum number of choices Discord shows for an option.
+pub const MAX_CHOICES: usize = 25;
+
+#[derive(Debug, Clone)]
+pub enum ChoiceValue {
+    String(String),
+    Integer(i64),
+    Number(f64),
+}
+
+/// Suggestion offered for an option while the user is typing it.\
+/// Value has to match the type of the option.
+#[derive(Debug, Clone)]
+pub struct Choice {
+    name: String,
+    value: ChoiceValue,
+}
+impl Choice {
+    pub fn string(name: impl ToString, value: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            value: ChoiceValue::String(value.to_string()),
+        }
+    }
+    pub fn int(name: impl ToString, value: i64) -> Self {
+        Self {
+            name: name.to_string(),
+            value: ChoiceValue::Integer(value),
+        }
+    }
+    pub fn number(name: impl ToString, value: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            value: ChoiceValue::Number(value),
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        self.name.clone()
+    }
+    pub fn get_value(&self) -> ChoiceValue {
+        self.value.clone()
+    }
+
+    pub(crate) fn add_to(self, response: &mut CreateAutocompleteResponse) {
+        match self.value {
+            ChoiceValue::String(value) => response.add_string_choice(self.name, value),
+            ChoiceValue::Integer(value) => response.add_int_choice(self.name, value),
+            ChoiceValue::Number(value) => response.add_number_choice(self.name, value),
+        };
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+/// Locale of the messages compiled into the bot.
+pub const DEFAULT_LOCALE: &str = "en-US";
+/// Messages compiled into the bot, so it can respond even without a `locales` directory.
+const BUILT_IN: &str = include_str!("../../../locales/en-US/eternacore.ftl");
+
+/// Reasons why a catalog can't be loaded.
+#[derive(Debug)]
+pub enum CatalogError {
+    /// Directory or file couldn't be read.
+    Io(PathBuf, io::Error),
+    /// Name of a directory isn't a valid locale.
+    InvalidLocale(String),
+    /// File isn't valid Fluent.
+    Syntax(PathBuf, String),
+}
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogError::Io(path, why) => write!(f, "Couldn't read `{}`: {}", path.display(), why),
+            CatalogError::InvalidLocale(locale) => write!(f, "`{}` isn't a valid locale", locale),
+            CatalogError::Syntax(path, why) => {
+                write!(f, "Couldn't parse `{}`: {}", path.display(), why)
+            }
+        }
+    }
+}
+impl std::error::Error for CatalogError {}
+
+/// Messages written in [Fluent](https://projectfluent.org), keyed by locale.\
+/// Rendering falls back to the language without a region (`es` for `es-ES`), then to the default locale.
+pub struct Catalog {
+    default_locale: String,
+    bundles: HashMap<String, FluentBundle<FluentResource>>,
+}
+impl Catalog {
+    /// Creates a catalog holding only the messages compiled into the bot.
+    pub fn new() -> Self {
+        let mut catalog = Self {
+            default_locale: DEFAULT_LOCALE.to_string(),
+            bundles: HashMap::new(),
+        };
+        // The built-in file ships with the source, so it always parses
+        catalog
+            .add(DEFAULT_LOCALE, BUILT_IN.to_string(), Path::new("built-in"))
+            .unwrap();
+        catalog
+    }
+
+    /// Returns a shared catalog holding only the built-in messages,
+    /// used where no other catalog is at hand, e.g. to display errors in logs.
+    pub fn built_in() -> &'static Catalog {
+        static BUILT_IN_CATALOG: OnceLock<Catalog> = OnceLock::new();
+        BUILT_IN_CATALOG.get_or_init(Catalog::new)
+    }
+
+    /// Loads `.ftl` files from subdirectories of `directory` named after Discord locales,
+    /// e.g. `locales/de/eternacore.ftl`. Loaded messages override the built-in ones.\
+    /// A missing directory leaves only the built-in messages.
+    pub fn load(directory: impl AsRef<Path>, default_locale: &str) -> Result<Self, CatalogError> {
+        let directory = directory.as_ref();
+        let mut catalog = Self::new();
+        catalog.default_locale = default_locale.to_string();
+
+        let locales = match fs::read_dir(directory) {
+            Ok(locales) => locales,
+            Err(why) if why.kind() == io::ErrorKind::NotFound => return Ok(catalog),
+            Err(why) => return Err(CatalogError::Io(directory.to_path_buf(), why)),
+        };
+        for locale in locales {
+            let locale = locale.map_err(|why| CatalogError::Io(directory.to_path_buf(), why))?;
+            let path = locale.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let name = locale.file_name().to_string_lossy().to_string();
+            let files = fs::read_dir(&path).map_err(|why| CatalogError::Io(path.clone(), why))?;
+            for file in files {
+                let path = file
+                    .map_err(|why| CatalogError::Io(path.clone(), why))?
+                    .path();
+                if path.extension().is_none_or(|extension| extension != "ftl") {
+                    continue;
+                }
+                let source =
+                    fs::read_to_string(&path).map_err(|why| CatalogError::Io(path.clone(), why))?;
+                catalog.add(&name, source, &path)?;
+            }
+        }
+        Ok(catalog)
+    }
+
+    fn add(&mut self, locale: &str, source: String, path: &Path) -> Result<(), CatalogError> {
+        let resource = FluentResource::try_new(source).map_err(|(_, errors)| {
+            CatalogError::Syntax(path.to_path_buf(), join_errors(&errors))
+        })?;
+        if !self.bundles.contains_key(locale) {
+            let identifier: LanguageIdentifier = locale
+                .parse()
+                .map_err(|_| CatalogError::InvalidLocale(locale.to_string()))?;
+            let mut bundle = FluentBundle::new_concurrent(vec![identifier]);
+            // Isolation marks around arguments show up as garbage in some Discord clients
+            bundle.set_use_isolating(false);
+            self.bundles.insert(locale.to_string(), bundle);
+        }
+        self.bundles
+            .get_mut(locale)
+            .unwrap()
+            .add_resource_overriding(resource);
+        Ok(())
+    }
+
+    /// Locales which have at least one message.
+    pub fn locales(&self) -> Vec<String> {
+        self.bundles.keys().cloned().collect()
+    }
+
+    /// Renders the message `key` in `locale`, falling back to other locales if it's missing there.\
+    /// Returns the key itself if no locale has such message.
+    pub fn render(&self, locale: Option<&str>, key: &str, args: Option<&FluentArgs>) -> String {
+        let language = locale.and_then(|locale| locale.split('-').next());
+        let candidates = [
+            locale,
+            language,
+            Some(&self.default_locale),
+            Some(DEFAULT_LOCALE),
+        ];
+
+        for locale in candidates.into_iter().flatten() {
+            let bundle = match self.bundles.get(locale) {
+                Some(bundle) => bundle,
+                None => continue,
+            };
+            let pattern = match bundle.get_message(key).and_then(|message| message.value()) {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+            let mut errors = Vec::new();
+            let rendered = bundle.format_pattern(pattern, args, &mut errors);
+            if !errors.is_empty() {
+                warn!(
+                    "Couldn't fully render message `{}` in `{}`: {}",
+                    key,
+                    locale,
+                    join_errors(&errors)
+                );
+            }
+            return rendered.to_string();
+        }
+        warn!("Message `{}` doesn't exist in any locale", key);
+        key.to_string()
+    }
+}
+impl Default for Catalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn join_errors(errors: &[impl fmt::Display]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
@@ -0,0 +1,87 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::sync::OnceLock;
+
+use serenity::{
+    http::Http,
+    model::{channel::Channel, id::UserId},
+};
+use tokio::sync::OnceCell;
+
+use super::{Command, CommandResult, Error, Request};
+
+/// Evaluates the checks declared on `command` against the user who invoked it,
+/// ran by the dispatcher before [Command::run].
+pub async fn evaluate(command: &dyn Command, req: &Request) -> CommandResult {
+    if command.guild_only() && req.guild.is_none() {
+        return Err(Error::GuildOnly);
+    }
+    if command.dm_only() && req.guild.is_some() {
+        return Err(Error::DmOnly);
+    }
+    if command.owner_only() && !is_owner(&req.http, req.user.id).await? {
+        return Err(Error::OwnerOnly);
+    }
+    if command.nsfw_only() && !is_nsfw(req).await? {
+        return Err(Error::NsfwOnly);
+    }
+
+    // Permissions are `None` outside of guilds, where they don't exist
+    let required = command.user_permissions();
+    if !required.is_empty() {
+        if let Some(permissions) = req.member_permissions().await? {
+            if !permissions.contains(required) {
+                return Err(Error::MissingPermissions(required - permissions));
+            }
+        }
+    }
+    let required = command.bot_permissions();
+    if !required.is_empty() {
+        if let Some(permissions) = req.app_permissions().await? {
+            if !permissions.contains(required) {
+                return Err(Error::MissingBotPermissions(required - permissions));
+            }
+        }
+    }
+
+    command.check(req).await
+}
+
+/// Checks if `user` owns the application or is a member of the team owning it.\
+/// Owners are fetched once and shared by every check, a failed fetch is tried again next time.
+pub async fn is_owner(http: &Http, user: UserId) -> Result<bool, Error> {
+    static OWNERS: OnceLock<OnceCell<Vec<UserId>>> = OnceLock::new();
+    let owners = OWNERS
+        .get_or_init(OnceCell::new)
+        .get_or_try_init(|| async {
+            let application = http.get_current_application_info().await?;
+            Ok::<_, Error>(match application.team {
+                Some(team) => team.members.iter().map(|member| member.user.id).collect(),
+                None => vec![application.owner.id],
+            })
+        })
+        .await?;
+    Ok(owners.contains(&user))
+}
+
+/// Direct messages are treated as age-restricted, just like Discord does.
+async fn is_nsfw(req: &Request) -> Result<bool, Error> {
+    Ok(match req.channel.to_channel(&req.http).await? {
+        Channel::Guild(channel) => channel.nsfw,
+        _ => true,
+    })
+}
@@ -14,8 +14,12 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
-use super::{Request, Response};
-use serenity::builder::CreateApplicationCommand;
+use super::{Choice, CommandResult, Cooldown, Request, Response};
+use serenity::{
+    builder::CreateApplicationCommand,
+    json::Value,
+    model::{application::command::CommandType, id::GuildId, Permissions},
+};
 
 /// Representation of both a **message** and an **interaction** command.
 #[async_trait]
@@ -25,16 +29,63 @@ pub trait Command: Send + Sync + 'static {
     ///  - Lowercase-only
     ///  - No spaces
     fn name<'s>(&self) -> &'s str;
+    /// Other names the command can be invoked by as a message command.
+    fn aliases<'s>(&self) -> Vec<&'s str> {
+        Vec::new()
+    }
     /// Description of the command which will be shown in slash command preview or `help` message command.
     fn description<'s>(&self) -> &'s str {
         ""
     }
 
+    /// Names of the command in other locales as `(locale, name)` pairs, e.g. `("de", "löschen")`.\
+    /// Discord shows them to users whose client is set to the locale.
+    fn localized_names<'s>(&self) -> Vec<(&'s str, &'s str)> {
+        Vec::new()
+    }
+    /// Descriptions of the command in other locales as `(locale, description)` pairs.
+    fn localized_descriptions<'s>(&self) -> Vec<(&'s str, &'s str)> {
+        Vec::new()
+    }
+
+    /// Type of the command, [CommandType::User] and [CommandType::Message] commands are shown
+    /// in the context menu of a user or a message and can't take options.\
+    /// Unlike slash commands, their names can contain spaces and uppercase letters.
+    fn kind(&self) -> CommandType {
+        CommandType::ChatInput
+    }
+
     fn default_layout<'s>(
         &self,
         command: &'s mut CreateApplicationCommand,
     ) -> &'s mut CreateApplicationCommand {
-        command.name(self.name()).description(self.description())
+        command.name(self.name());
+        // Context menu commands must not have a description
+        match self.kind() {
+            CommandType::ChatInput => {
+                command.description(self.description());
+                for (locale, description) in self.localized_descriptions() {
+                    command.description_localized(locale, description);
+                }
+            }
+            kind => {
+                command.kind(kind);
+            }
+        };
+        for (locale, name) in self.localized_names() {
+            command.name_localized(locale, name);
+        }
+        // Hides the command from users who can't use it anyway
+        if !self.user_permissions().is_empty() {
+            command.default_member_permissions(self.user_permissions());
+        }
+        if self.guild_only() {
+            command.dm_permission(false);
+        }
+        if self.nsfw_only() {
+            command.0.insert("nsfw", Value::Bool(true));
+        }
+        command
     }
 
     /// A layout of a command and definition of acceptable arguments.
@@ -45,15 +96,92 @@ pub trait Command: Send + Sync + 'static {
         self.default_layout(command)
     }
 
+    /// Returns the subcommand or subcommand group called `name`, only [Group](super::Group)s have any.\
+    /// Lets the dispatcher evaluate checks and cooldowns of the invoked subcommand before running anything.
+    fn subcommand(&self, _name: &str) -> Option<&dyn Command> {
+        None
+    }
+
+    /// Permissions the user needs in the channel to run the command.
+    fn user_permissions(&self) -> Permissions {
+        Permissions::empty()
+    }
+    /// Permissions the bot needs in the channel to run the command.
+    fn bot_permissions(&self) -> Permissions {
+        Permissions::empty()
+    }
+    /// Whether the command can only be used in guilds.
+    fn guild_only(&self) -> bool {
+        false
+    }
+    /// Whether the command can only be used in direct messages.
+    fn dm_only(&self) -> bool {
+        false
+    }
+    /// Whether the command can only be used by owners of the bot application.
+    fn owner_only(&self) -> bool {
+        false
+    }
+    /// Whether the command can only be used in age-restricted channels.
+    fn nsfw_only(&self) -> bool {
+        false
+    }
+    /// Guilds the command is registered in instead of globally, empty makes it available everywhere.
+    fn guilds(&self) -> Vec<GuildId> {
+        Vec::new()
+    }
+    /// Asynchronous check which will be ran after the declared ones, an error prevents the command from running.
+    async fn check(&self, _req: &Request) -> CommandResult {
+        Ok(())
+    }
+
+    /// Limits how often the command can be used.
+    fn cooldown(&self) -> Option<Cooldown> {
+        None
+    }
+
+    /// Whether the command usually takes longer than 3 seconds to respond,
+    /// such commands are deferred before being ran.
+    fn slow(&self) -> bool {
+        false
+    }
+    /// Whether responses deferred by the framework are only visible to the user.
+    fn ephemeral(&self) -> bool {
+        false
+    }
+
     /// Asynchronous method which will be ran when user executes the command.\
-    /// While [Response] structure is command model agnostic, you can retrieve specific model by using [`if let`](https://doc.rust-lang.org/book/ch06-03-if-let.html) syntax.
-    async fn run(&self, req: Request, res: Response);
+    /// While [Response] structure is command model agnostic, you can retrieve specific model by using [`if let`](https://doc.rust-lang.org/book/ch06-03-if-let.html) syntax.\
+    /// Returned errors are reported to the user by the [ErrorHandler](super::ErrorHandler).
+    async fn run(&self, req: Request, res: Response) -> CommandResult;
+
+    /// Asynchronous method which will be ran while user types an option registered with autocompletion.\
+    /// Receives the name of the focused option and its partial value, only the first 25 choices are shown.
+    async fn autocomplete(&self, _req: Request, _option: &str, _value: &str) -> Vec<Choice> {
+        Vec::new()
+    }
+
+    /// Asynchronous method which will be ran when user interacts with a component
+    /// whose custom ID was created by [Request::custom_id].\
+    /// Interacted component can be retrieved with [Request::component].
+    /// Checks of the command are evaluated first, cooldowns aren't taken.
+    async fn component(&self, _req: Request, _res: Response) -> CommandResult {
+        Ok(())
+    }
+
+    /// Asynchronous method which will be ran when user submits a modal
+    /// whose custom ID was created by [Request::custom_id].\
+    /// Submitted values can be retrieved with [Request::submission].
+    /// Checks of the command are evaluated first, cooldowns aren't taken.
+    async fn modal(&self, _req: Request, _res: Response) -> CommandResult {
+        Ok(())
+    }
 }
 impl<T: Command + ?Sized> Register for T {
     fn register<'reg>(
         &'reg self,
         command: &'reg mut CreateApplicationCommand,
-    ) -> &mut CreateApplicationCommand {
+    ) -> &'reg mut CreateApplicationCommand {
         self.layout(command)
     }
 }
@@ -61,5 +189,5 @@ pub trait Register {
     fn register<'reg>(
         &'reg self,
         command: &'reg mut CreateApplicationCommand,
-    ) -> &mut CreateApplicationCommand;
+    ) -> &'reg mut CreateApplicationCommand;
 }
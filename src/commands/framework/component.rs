@@ -0,0 +1,339 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use serenity::{
+    builder::{CreateActionRow, CreateComponents},
+    model::channel::ReactionType,
+};
+
+pub use serenity::model::application::component::ButtonStyle;
+
+use super::{CommandResult, Request, Response};
+
+/// Separates the namespace of a custom ID from the ID itself, e.g. `purge:confirm`.
+pub const SEPARATOR: char = ':';
+
+/// Prefixes `id` with a namespace, so the click can be routed back to its owner.\
+/// Namespace is either a path of a command encoded by [encode_path] (`mod case view`) or a namespace of a [ComponentHandler].
+pub fn custom_id(namespace: &str, id: &str) -> String {
+    format!("{}{}{}", namespace, SEPARATOR, id)
+}
+
+/// Splits a custom ID into its namespace and the ID itself.
+pub fn split_custom_id(custom_id: &str) -> (&str, &str) {
+    custom_id.split_once(SEPARATOR).unwrap_or((custom_id, ""))
+}
+
+/// Joins a command path into a namespace, e.g. `mod case view`.\
+/// Names of context menu commands can contain spaces and separators,
+/// so they're escaped as `%20`, `%3A` and `%25` for `%` itself.
+pub fn encode_path(path: &[String]) -> String {
+    path.iter()
+        .map(|name| {
+            name.replace('%', "%25")
+                .replace(' ', "%20")
+                .replace(SEPARATOR, "%3A")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Splits a namespace into the command path it was made from by [encode_path],
+/// the first segment is the owner of the custom ID.
+pub fn decode_path(namespace: &str) -> Vec<String> {
+    namespace
+        .split(' ')
+        .map(|segment| {
+            let mut name = String::with_capacity(segment.len());
+            let mut rest = segment;
+            while let Some(index) = rest.find('%') {
+                name.push_str(&rest[..index]);
+                rest = &rest[index..];
+                let escaped = match rest.get(..3) {
+                    Some("%20") => ' ',
+                    Some("%3A") => SEPARATOR,
+                    Some("%25") => '%',
+                    _ => {
+                        name.push('%');
+                        rest = &rest[1..];
+                        continue;
+                    }
+                };
+                name.push(escaped);
+                rest = &rest[3..];
+            }
+            name.push_str(rest);
+            name
+        })
+        .collect()
+}
+
+/// Handler of components and modals which don't belong to any command,
+/// e.g. buttons of a persistent role menu.
+#[async_trait]
+pub trait ComponentHandler: Send + Sync + 'static {
+    /// Namespace of custom IDs handled by this handler.\
+    /// Naming rules:
+    ///  - No spaces
+    ///  - Must not collide with command names
+    fn namespace<'s>(&self) -> &'s str;
+
+    /// Asynchronous method which will be ran when user interacts with a component in this namespace.\
+    /// Interacted component can be retrieved with [Request::component].
+    async fn run(&self, req: Request, res: Response) -> CommandResult;
+
+    /// Asynchronous method which will be ran when user submits a modal in this namespace.\
+    /// Submitted values can be retrieved with [Request::submission].
+    async fn modal(&self, _req: Request, _res: Response) -> CommandResult {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ButtonAction {
+    CustomId(String, ButtonStyle),
+    Url(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Button {
+    action: ButtonAction,
+    label: Option<String>,
+    emoji: Option<ReactionType>,
+    disabled: bool,
+}
+impl Button {
+    /// Creates a button which sends an interaction, `custom_id` has to be namespaced.
+    pub fn new(custom_id: impl ToString, style: ButtonStyle) -> Self {
+        Self {
+            action: ButtonAction::CustomId(custom_id.to_string(), style),
+            label: None,
+            emoji: None,
+            disabled: false,
+        }
+    }
+    /// Creates a button which opens a link instead of sending an interaction.
+    pub fn link(url: impl ToString) -> Self {
+        Self {
+            action: ButtonAction::Url(url.to_string()),
+            label: None,
+            emoji: None,
+            disabled: false,
+        }
+    }
+
+    pub fn label(mut self, label: impl ToString) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+    pub fn emoji(mut self, emoji: impl Into<ReactionType>) -> Self {
+        self.emoji = Some(emoji.into());
+        self
+    }
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    fn build(&self, row: &mut CreateActionRow) {
+        row.create_button(|button| {
+            match &self.action {
+                ButtonAction::CustomId(custom_id, style) => {
+                    button.custom_id(custom_id).style(*style)
+                }
+                ButtonAction::Url(url) => button.url(url).style(ButtonStyle::Link),
+            };
+            if let Some(label) = &self.label {
+                button.label(label);
+            }
+            if let Some(emoji) = &self.emoji {
+                button.emoji(emoji.clone());
+            }
+            button.disabled(self.disabled)
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SelectOption {
+    label: String,
+    value: String,
+    description: Option<String>,
+    default: bool,
+}
+impl SelectOption {
+    pub fn new(label: impl ToString, value: impl ToString) -> Self {
+        Self {
+            label: label.to_string(),
+            value: value.to_string(),
+            description: None,
+            default: false,
+        }
+    }
+
+    pub fn description(mut self, description: impl ToString) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+    /// Selects the option by default.
+    pub fn default(mut self, default: bool) -> Self {
+        self.default = default;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SelectMenu {
+    custom_id: String,
+    placeholder: Option<String>,
+    options: Vec<SelectOption>,
+    min_values: Option<u64>,
+    max_values: Option<u64>,
+    disabled: bool,
+}
+impl SelectMenu {
+    /// Creates a select menu, `custom_id` has to be namespaced.
+    pub fn new(custom_id: impl ToString) -> Self {
+        Self {
+            custom_id: custom_id.to_string(),
+            placeholder: None,
+            options: Vec::new(),
+            min_values: None,
+            max_values: None,
+            disabled: false,
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl ToString) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+    pub fn option(mut self, option: SelectOption) -> Self {
+        self.options.push(option);
+        self
+    }
+    /// Sets how many options the user has to pick, `1..=1` by default.
+    pub fn values(mut self, min: u64, max: u64) -> Self {
+        self.min_values = Some(min);
+        self.max_values = Some(max);
+        self
+    }
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    fn build(&self, row: &mut CreateActionRow) {
+        row.create_select_menu(|menu| {
+            menu.custom_id(&self.custom_id)
+                .disabled(self.disabled)
+                .options(|options| {
+                    for option in self.options.iter() {
+                        options.create_option(|created| {
+                            created
+                                .label(&option.label)
+                                .value(&option.value)
+                                .default_selection(option.default);
+                            if let Some(description) = &option.description {
+                                created.description(description);
+                            }
+                            created
+                        });
+                    }
+                    options
+                });
+            if let Some(placeholder) = &self.placeholder {
+                menu.placeholder(placeholder);
+            }
+            if let Some(min_values) = self.min_values {
+                menu.min_values(min_values);
+            }
+            if let Some(max_values) = self.max_values {
+                menu.max_values(max_values);
+            }
+            menu
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+    Button(Button),
+    SelectMenu(SelectMenu),
+}
+
+/// Row of up to 5 buttons or a single select menu.
+#[derive(Debug, Clone, Default)]
+pub struct ActionRow {
+    items: Vec<Item>,
+}
+impl ActionRow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn button(mut self, button: Button) -> Self {
+        self.items.push(Item::Button(button));
+        self
+    }
+    pub fn select_menu(mut self, select_menu: SelectMenu) -> Self {
+        self.items.push(Item::SelectMenu(select_menu));
+        self
+    }
+
+    pub(crate) fn build(&self, components: &mut CreateComponents) {
+        components.create_action_row(|row| {
+            for item in self.items.iter() {
+                match item {
+                    Item::Button(button) => button.build(row),
+                    Item::SelectMenu(select_menu) => select_menu.build(row),
+                }
+            }
+            row
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(path: &[&str], id: &str) {
+        let path: Vec<String> = path.iter().map(|name| name.to_string()).collect();
+        let custom_id = custom_id(&encode_path(&path), id);
+        let (namespace, split_id) = split_custom_id(&custom_id);
+        assert_eq!(decode_path(namespace), path);
+        assert_eq!(split_id, id);
+    }
+
+    #[test]
+    fn paths_survive_custom_ids() {
+        round_trip(&["purge"], "confirm");
+        round_trip(&["mod", "case", "view"], "next:2");
+        round_trip(&["Report message"], "confirm");
+        round_trip(&["Ratio: 100%", "a%20b"], "");
+    }
+
+    #[test]
+    fn plain_paths_are_kept_readable() {
+        let path = ["mod".to_string(), "case".to_string(), "view".to_string()];
+        assert_eq!(custom_id(&encode_path(&path), "next"), "mod case view:next");
+        assert_eq!(
+            custom_id(&encode_path(&["Report message".to_string()]), "confirm"),
+            "Report%20message:confirm"
+        );
+    }
+}
@@ -0,0 +1,352 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
+
+use super::{checks, Error, Request};
+
+/// Buckets are pruned of expired entries once there are more of them than this.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// What the uses of a command are counted per.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Every user, wherever they use the command.
+    User,
+    /// Every user in every guild separately, the same as [Scope::User] in direct messages.
+    Member,
+    /// Every channel, shared by everyone in it.
+    Channel,
+    /// Every guild, shared by everyone in it. The same as [Scope::Channel] in direct messages.
+    Guild,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Uses are reset all at once when the window started by the first use ends.
+    FixedWindow,
+    /// Uses are refilled one by one at an even rate.
+    TokenBucket,
+}
+
+/// Limits how often a command can be used.
+#[derive(Debug, Clone, Copy)]
+pub struct Cooldown {
+    scope: Scope,
+    strategy: Strategy,
+    uses: u32,
+    per: Duration,
+}
+impl Cooldown {
+    /// Allows `uses` uses per window of `per`, starting with the first one.\
+    /// Panics if `uses` is zero, such command couldn't be used at all.
+    pub fn fixed_window(scope: Scope, uses: u32, per: Duration) -> Self {
+        assert!(uses > 0, "cooldown must allow at least one use");
+        Self {
+            scope,
+            strategy: Strategy::FixedWindow,
+            uses,
+            per,
+        }
+    }
+    /// Allows bursts of up to `uses` uses, refilled evenly over `per`.\
+    /// Panics if `uses` is zero, such command couldn't be used at all.
+    pub fn token_bucket(scope: Scope, uses: u32, per: Duration) -> Self {
+        assert!(uses > 0, "cooldown must allow at least one use");
+        Self {
+            scope,
+            strategy: Strategy::TokenBucket,
+            uses,
+            per,
+        }
+    }
+
+    pub fn get_scope(&self) -> Scope {
+        self.scope
+    }
+    pub fn get_strategy(&self) -> Strategy {
+        self.strategy
+    }
+    pub fn get_uses(&self) -> u32 {
+        self.uses
+    }
+    pub fn get_per(&self) -> Duration {
+        self.per
+    }
+
+    fn key(&self, req: &Request) -> Key {
+        match (self.scope, req.guild) {
+            (Scope::User, _) | (Scope::Member, None) => Key::User(req.user.id),
+            (Scope::Member, Some(guild)) => Key::Member(guild, req.user.id),
+            (Scope::Channel, _) | (Scope::Guild, None) => Key::Channel(req.channel),
+            (Scope::Guild, Some(guild)) => Key::Guild(guild),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Key {
+    User(UserId),
+    Member(GuildId, UserId),
+    Channel(ChannelId),
+    Guild(GuildId),
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Uses taken in the current window, or tokens left in the bucket.
+    uses: f64,
+    /// Start of the window, or last time the bucket was refilled.
+    since: Instant,
+    /// Time after which the bucket is as good as new.
+    expires: Instant,
+}
+impl Bucket {
+    fn new(cooldown: &Cooldown, now: Instant) -> Self {
+        Self {
+            uses: match cooldown.strategy {
+                Strategy::FixedWindow => 0.0,
+                Strategy::TokenBucket => f64::from(cooldown.uses),
+            },
+            since: now,
+            expires: now,
+        }
+    }
+
+    /// Takes a use at `now`, returns how long is left until the next use if none are left.
+    fn take(&mut self, cooldown: &Cooldown, now: Instant) -> Option<Duration> {
+        let uses = f64::from(cooldown.uses);
+        match cooldown.strategy {
+            Strategy::FixedWindow => {
+                if now >= self.since + cooldown.per {
+                    self.uses = 0.0;
+                    self.since = now;
+                }
+                if self.uses >= uses {
+                    return Some(self.since + cooldown.per - now);
+                }
+                self.uses += 1.0;
+                self.expires = self.since + cooldown.per;
+            }
+            Strategy::TokenBucket => {
+                // Tokens refilled per second
+                let rate = uses / cooldown.per.as_secs_f64();
+                self.uses = (self.uses + (now - self.since).as_secs_f64() * rate).min(uses);
+                self.since = now;
+                if self.uses < 1.0 {
+                    return Some(Duration::from_secs_f64((1.0 - self.uses) / rate));
+                }
+                self.uses -= 1.0;
+                self.expires = now + Duration::from_secs_f64((uses - self.uses) / rate);
+            }
+        }
+        None
+    }
+}
+
+/// Tracks uses of commands with a [Cooldown].
+pub struct Cooldowns {
+    buckets: Mutex<HashMap<(String, Key), Bucket>>,
+    exempt_roles: Vec<RoleId>,
+    exempt_owners: bool,
+}
+impl Cooldowns {
+    pub fn new(exempt_roles: Vec<RoleId>, exempt_owners: bool) -> Self {
+        Self {
+            buckets: Mutex::new(HashMap::new()),
+            exempt_roles,
+            exempt_owners,
+        }
+    }
+
+    /// Takes a use of `command`, failing with the time left until the next one if none are left.\
+    /// Users with exempt roles aren't limited at all and don't take uses shared with others,
+    /// owners are only looked up once they run out of uses, so limited users don't cost a request.
+    pub async fn hit(
+        &self,
+        command: &str,
+        cooldown: &Cooldown,
+        req: &Request,
+    ) -> Result<(), Error> {
+        if req
+            .roles()
+            .iter()
+            .any(|role| self.exempt_roles.contains(role))
+        {
+            return Ok(());
+        }
+        match self.take(command, cooldown, cooldown.key(req)) {
+            Some(_) if self.exempt_owners && self.is_owner(req).await => Ok(()),
+            Some(remaining) => Err(Error::Cooldown(remaining)),
+            None => Ok(()),
+        }
+    }
+
+    /// Failing to look owners up leaves the cooldown in place instead of failing the command.
+    async fn is_owner(&self, req: &Request) -> bool {
+        checks::is_owner(&req.http, req.user.id)
+            .await
+            .unwrap_or_else(|why| {
+                warn!(
+                    "Couldn't check if `{}` owns the bot: {}",
+                    req.user.id,
+                    why.to_string()
+                );
+                false
+            })
+    }
+
+    /// Returns how long is left until the next use if none are left.
+    fn take(&self, command: &str, cooldown: &Cooldown, key: Key) -> Option<Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| bucket.expires > now);
+        }
+
+        buckets
+            .entry((command.to_string(), key))
+            .or_insert_with(|| Bucket::new(cooldown, now))
+            .take(cooldown, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serenity::{http::HttpBuilder, model::id::GuildId};
+
+    use super::*;
+    use crate::commands::framework::request::MemberView;
+
+    fn seconds(duration: Option<Duration>) -> Option<f64> {
+        duration.map(|duration| (duration.as_secs_f64() * 1000.0).round() / 1000.0)
+    }
+
+    #[test]
+    fn fixed_window_resets_all_at_once() {
+        let cooldown = Cooldown::fixed_window(Scope::User, 2, Duration::from_secs(10));
+        let start = Instant::now();
+        let mut bucket = Bucket::new(&cooldown, start);
+
+        assert_eq!(bucket.take(&cooldown, start), None);
+        assert_eq!(bucket.take(&cooldown, start + Duration::from_secs(1)), None);
+        assert_eq!(
+            seconds(bucket.take(&cooldown, start + Duration::from_secs(4))),
+            Some(6.0)
+        );
+        assert_eq!(
+            bucket.take(&cooldown, start + Duration::from_secs(10)),
+            None
+        );
+        assert_eq!(
+            bucket.take(&cooldown, start + Duration::from_secs(11)),
+            None
+        );
+        assert_eq!(
+            seconds(bucket.take(&cooldown, start + Duration::from_secs(12))),
+            Some(8.0)
+        );
+    }
+
+    #[test]
+    fn token_bucket_refills_evenly() {
+        let cooldown = Cooldown::token_bucket(Scope::User, 2, Duration::from_secs(10));
+        let start = Instant::now();
+        let mut bucket = Bucket::new(&cooldown, start);
+
+        assert_eq!(bucket.take(&cooldown, start), None);
+        assert_eq!(bucket.take(&cooldown, start), None);
+        assert_eq!(seconds(bucket.take(&cooldown, start)), Some(5.0));
+        assert_eq!(
+            seconds(bucket.take(&cooldown, start + Duration::from_secs(2))),
+            Some(3.0)
+        );
+        assert_eq!(bucket.take(&cooldown, start + Duration::from_secs(5)), None);
+        assert_eq!(
+            seconds(bucket.take(&cooldown, start + Duration::from_secs(5))),
+            Some(5.0)
+        );
+        // Never holds more than `uses` tokens
+        let later = start + Duration::from_secs(100);
+        assert_eq!(bucket.take(&cooldown, later), None);
+        assert_eq!(bucket.take(&cooldown, later), None);
+        assert_eq!(seconds(bucket.take(&cooldown, later)), Some(5.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_zero_uses() {
+        Cooldown::token_bucket(Scope::User, 0, Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn exempt_users_dont_take_shared_uses() {
+        let exempt = RoleId(1);
+        let guild = GuildId(2);
+        let cooldowns = Cooldowns::new(vec![exempt], false);
+        let cooldown = Cooldown::fixed_window(Scope::Guild, 1, Duration::from_secs(60));
+        let member = MemberView {
+            guild,
+            nick: None,
+            roles: vec![exempt],
+            joined_at: None,
+            premium_since: None,
+            pending: false,
+            permissions: None,
+        };
+
+        let moderator = Request::builder("purge")
+            .guild(guild)
+            .member(member)
+            .build();
+        for _ in 0..3 {
+            cooldowns.hit("purge", &cooldown, &moderator).await.unwrap();
+        }
+        let user = Request::builder("purge").guild(guild).build();
+        cooldowns.hit("purge", &cooldown, &user).await.unwrap();
+        assert!(matches!(
+            cooldowns.hit("purge", &cooldown, &user).await,
+            Err(Error::Cooldown(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn owners_arent_looked_up_until_out_of_uses() {
+        // Nothing listens there, so looking owners up fails right away
+        let http = HttpBuilder::new("")
+            .proxy("http://127.0.0.1:1")
+            .unwrap()
+            .ratelimiter_disabled(true)
+            .build();
+        let cooldowns = Cooldowns::new(Vec::new(), true);
+        let cooldown = Cooldown::fixed_window(Scope::User, 1, Duration::from_secs(60));
+        let req = Request::builder("ping").http(Arc::new(http)).build();
+
+        cooldowns.hit("ping", &cooldown, &req).await.unwrap();
+        assert!(matches!(
+            cooldowns.hit("ping", &cooldown, &req).await,
+            Err(Error::Cooldown(_))
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use serenity::{builder::CreateEmbed, model::Timestamp};
+
+pub use serenity::utils::Colour;
+
+#[derive(Debug, Clone)]
+struct Field {
+    name: String,
+    value: String,
+    inline: bool,
+}
+
+/// Rich content attached to a message, up to 10 embeds can be sent at once.
+#[derive(Debug, Clone, Default)]
+pub struct Embed {
+    title: Option<String>,
+    description: Option<String>,
+    url: Option<String>,
+    colour: Option<Colour>,
+    fields: Vec<Field>,
+    author: Option<String>,
+    author_icon: Option<String>,
+    footer: Option<String>,
+    footer_icon: Option<String>,
+    image: Option<String>,
+    thumbnail: Option<String>,
+    timestamp: Option<Timestamp>,
+}
+impl Embed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl ToString) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+    pub fn description(mut self, description: impl ToString) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+    /// Turns the title into a link.
+    pub fn url(mut self, url: impl ToString) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+    /// Colour of the left-hand side of the embed.
+    pub fn colour(mut self, colour: impl Into<Colour>) -> Self {
+        self.colour = Some(colour.into());
+        self
+    }
+    /// Adds a field, inline fields are shown side by side. Up to 25 fields can be added.
+    pub fn field(mut self, name: impl ToString, value: impl ToString, inline: bool) -> Self {
+        self.fields.push(Field {
+            name: name.to_string(),
+            value: value.to_string(),
+            inline,
+        });
+        self
+    }
+    pub fn author(mut self, name: impl ToString) -> Self {
+        self.author = Some(name.to_string());
+        self
+    }
+    pub fn author_icon(mut self, url: impl ToString) -> Self {
+        self.author_icon = Some(url.to_string());
+        self
+    }
+    pub fn footer(mut self, text: impl ToString) -> Self {
+        self.footer = Some(text.to_string());
+        self
+    }
+    pub fn footer_icon(mut self, url: impl ToString) -> Self {
+        self.footer_icon = Some(url.to_string());
+        self
+    }
+    /// Large image below the fields, `attachment://filename` refers to a file attached to the message.
+    pub fn image(mut self, url: impl ToString) -> Self {
+        self.image = Some(url.to_string());
+        self
+    }
+    /// Small image in the top right corner, `attachment://filename` refers to a file attached to the message.
+    pub fn thumbnail(mut self, url: impl ToString) -> Self {
+        self.thumbnail = Some(url.to_string());
+        self
+    }
+    /// Time shown next to the footer, e.g. [Timestamp::now].
+    pub fn timestamp(mut self, timestamp: impl Into<Timestamp>) -> Self {
+        self.timestamp = Some(timestamp.into());
+        self
+    }
+
+    pub(crate) fn build(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        if let Some(title) = &self.title {
+            embed.title(title);
+        }
+        if let Some(description) = &self.description {
+            embed.description(description);
+        }
+        if let Some(url) = &self.url {
+            embed.url(url);
+        }
+        if let Some(colour) = self.colour {
+            embed.colour(colour);
+        }
+        for field in self.fields.iter() {
+            embed.field(&field.name, &field.value, field.inline);
+        }
+        if let Some(name) = &self.author {
+            embed.author(|author| {
+                if let Some(icon) = &self.author_icon {
+                    author.icon_url(icon);
+                }
+                author.name(name)
+            });
+        }
+        if let Some(text) = &self.footer {
+            embed.footer(|footer| {
+                if let Some(icon) = &self.footer_icon {
+                    footer.icon_url(icon);
+                }
+                footer.text(text)
+            });
+        }
+        if let Some(image) = &self.image {
+            embed.image(image);
+        }
+        if let Some(thumbnail) = &self.thumbnail {
+            embed.thumbnail(thumbnail);
+        }
+        if let Some(timestamp) = self.timestamp {
+            embed.timestamp(timestamp);
+        }
+        embed
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{fmt, time::Duration};
+
+use fluent_bundle::FluentArgs;
+use serenity::model::Permissions;
+
+use super::{arguments::ArgumentError, parser::ParseError, Response};
+
+/// Result of running a command, component or modal handler.
+pub type CommandResult = Result<(), Error>;
+
+/// Reason a command failed, shown to the user by an [ErrorHandler].
+#[derive(Debug)]
+pub enum Error {
+    /// The user lacks permissions required by the command.
+    MissingPermissions(Permissions),
+    /// The bot lacks permissions required by the command.
+    MissingBotPermissions(Permissions),
+    /// The command has been disabled at runtime.
+    Disabled,
+    /// The command can only be used in guilds.
+    GuildOnly,
+    /// The command can only be used in direct messages.
+    DmOnly,
+    /// The command can only be used by owners of the bot application.
+    OwnerOnly,
+    /// The command can only be used in age-restricted channels.
+    NsfwOnly,
+    /// The command can't be used by this user or in this channel, the message is shown as is.
+    CheckFailed(String),
+    /// The command was used too often, holds the time left until it can be used again.
+    Cooldown(Duration),
+    /// Option couldn't be extracted from the command.
+    Argument(ArgumentError),
+    /// Message command arguments couldn't be parsed.
+    Parse(ParseError),
+    /// Options or message command arguments are invalid, the message is shown as is.
+    InvalidArguments(String),
+    /// Discord rejected a request.
+    Http(serenity::Error),
+    /// Error which should be shown to the user as is.
+    User(String),
+    /// Bug or failure of something the user can't do anything about, it's logged and hidden from the user.
+    Internal(Box<dyn std::error::Error + Send + Sync>),
+    /// The command was stopped on purpose and the user shouldn't be told about it, e.g. by a blacklist.
+    Cancelled,
+}
+impl Error {
+    pub fn user(message: impl ToString) -> Self {
+        Self::User(message.to_string())
+    }
+    pub fn internal(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Self::Internal(error.into())
+    }
+
+    /// Checks if the error is caused by the bot rather than the user, so it should be logged.
+    pub fn is_internal(&self) -> bool {
+        matches!(self, Self::Http(_) | Self::Internal(_))
+    }
+
+    /// Message explaining the error to the user, rendered from the catalog of the response.
+    pub fn user_message(&self, res: &Response) -> String {
+        let render = |key: &str, args: Option<&FluentArgs>| res.render(key, args);
+        let mut args = FluentArgs::new();
+        match self {
+            Self::MissingPermissions(permissions) => {
+                args.set("permissions", permissions.to_string());
+                render("error-missing-permissions", Some(&args))
+            }
+            Self::MissingBotPermissions(permissions) => {
+                args.set("permissions", permissions.to_string());
+                render("error-missing-bot-permissions", Some(&args))
+            }
+            Self::Disabled => render("error-disabled", None),
+            Self::GuildOnly => render("error-guild-only", None),
+            Self::DmOnly => render("error-dm-only", None),
+            Self::OwnerOnly => render("error-owner-only", None),
+            Self::NsfwOnly => render("error-nsfw-only", None),
+            Self::Cooldown(remaining) => {
+                args.set("seconds", remaining.as_secs_f64().ceil());
+                render("error-cooldown", Some(&args))
+            }
+            Self::Argument(error) => error.render(&render),
+            Self::Parse(error) => error.render(&render),
+            Self::CheckFailed(message) | Self::InvalidArguments(message) | Self::User(message) => {
+                message.clone()
+            }
+            Self::Cancelled => String::new(),
+            Self::Http(_) | Self::Internal(_) => render("error-internal", None),
+        }
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingPermissions(permissions) => {
+                write!(f, "user is missing {} permissions", permissions)
+            }
+            Self::MissingBotPermissions(permissions) => {
+                write!(f, "bot is missing {} permissions", permissions)
+            }
+            Self::Disabled => f.write_str("command is disabled"),
+            Self::GuildOnly => f.write_str("command can only be used in guilds"),
+            Self::DmOnly => f.write_str("command can only be used in direct messages"),
+            Self::OwnerOnly => f.write_str("command can only be used by owners"),
+            Self::NsfwOnly => f.write_str("command can only be used in age-restricted channels"),
+            Self::Cooldown(remaining) => write!(f, "on cooldown for {:?}", remaining),
+            Self::Argument(error) => write!(f, "{}", error),
+            Self::Parse(error) => write!(f, "{}", error),
+            Self::CheckFailed(message) | Self::InvalidArguments(message) | Self::User(message) => {
+                f.write_str(message)
+            }
+            Self::Http(why) => write!(f, "{}", why),
+            Self::Internal(why) => write!(f, "{}", why),
+            Self::Cancelled => f.write_str("cancelled"),
+        }
+    }
+}
+impl std::error::Error for Error {}
+
+impl From<ArgumentError> for Error {
+    fn from(error: ArgumentError) -> Self {
+        Self::Argument(error)
+    }
+}
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+impl From<serenity::Error> for Error {
+    fn from(error: serenity::Error) -> Self {
+        Self::Http(error)
+    }
+}
+
+/// Reports failed commands, components and modals.
+#[async_trait]
+pub trait ErrorHandler: Send + Sync + 'static {
+    /// Asynchronous method which will be ran when `command` fails.
+    async fn handle(&self, command: &str, error: Error, res: Response);
+}
+
+/// Logs internal errors and replies to the user with an ephemeral explanation.
+pub struct DefaultErrorHandler;
+#[async_trait]
+impl ErrorHandler for DefaultErrorHandler {
+    async fn handle(&self, command: &str, error: Error, res: Response) {
+        if matches!(error, Error::Cancelled) {
+            return;
+        }
+        if error.is_internal() {
+            error!("Command `{}` failed: {}", command, error);
+        }
+        if let Err(why) = res
+            .send_ephemeral_message(error.user_message(&res), true)
+            .await
+        {
+            error!(
+                "Couldn't report the failure of command `{}`: {}",
+                command,
+                why.to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use serenity::{
+    builder::{CreateApplicationCommand, CreateApplicationCommandOption},
+    json::Value,
+    model::prelude::command::CommandOptionType,
+};
+
+use super::{Choice, Command, CommandResult, Error, Request, Response};
+
+/// Command which doesn't run anything by itself, but routes to its subcommands,
+/// e.g. `/mod warn` or `/mod case view`.
+///
+/// A group nested in another group is registered as a subcommand group, every other command
+/// is registered as a subcommand. Discord allows only one level of nesting below the root command.
+pub struct Group {
+    name: &'static str,
+    description: &'static str,
+    commands: Vec<Box<dyn Command>>,
+}
+impl Group {
+    pub fn new(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            description,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Adds a subcommand or a subcommand group.
+    pub fn command(mut self, command: impl Command) -> Self {
+        self.commands.push(Box::new(command));
+        self
+    }
+}
+
+#[async_trait]
+impl Command for Group {
+    fn name<'s>(&self) -> &'s str {
+        self.name
+    }
+    fn description<'s>(&self) -> &'s str {
+        self.description
+    }
+
+    fn layout<'s>(
+        &self,
+        command: &'s mut CreateApplicationCommand,
+    ) -> &'s mut CreateApplicationCommand {
+        self.default_layout(command);
+        for subcommand in self.commands.iter() {
+            command.add_option(to_subcommand_option(subcommand.as_ref()));
+        }
+        command
+    }
+
+    fn subcommand(&self, name: &str) -> Option<&dyn Command> {
+        self.commands
+            .iter()
+            .find(|defined_command| defined_command.name() == name)
+            .map(|defined_command| defined_command.as_ref())
+    }
+
+    /// Runs the invoked subcommand, its checks and cooldown have already been applied by the dispatcher.
+    async fn run(&self, req: Request, res: Response) -> CommandResult {
+        let subcommand = req.command().subcommand().ok_or_else(|| {
+            Error::internal(format!(
+                "command group `{}` was invoked without a subcommand",
+                self.name
+            ))
+        })?;
+
+        match self.subcommand(&subcommand.name()) {
+            Some(defined_command) => defined_command.run(req.with_command(subcommand), res).await,
+            None => Err(Error::internal(format!(
+                "command group `{}` has no subcommand `{}`",
+                self.name,
+                subcommand.name()
+            ))),
+        }
+    }
+
+    async fn autocomplete(&self, req: Request, option: &str, value: &str) -> Vec<Choice> {
+        let subcommand = match req.command().subcommand() {
+            Some(subcommand) => subcommand,
+            None => return Vec::new(),
+        };
+
+        for defined_command in self.commands.iter() {
+            if subcommand.name() == defined_command.name() {
+                return defined_command
+                    .autocomplete(req.with_command(subcommand), option, value)
+                    .await;
+            }
+        }
+        Vec::new()
+    }
+
+    async fn component(&self, req: Request, res: Response) -> CommandResult {
+        let command = req.command();
+        let name = match interacted_subcommand(&req) {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+
+        for defined_command in self.commands.iter() {
+            if name == defined_command.name() {
+                return defined_command
+                    .component(req.with_command(command.descend(&name)), res)
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn modal(&self, req: Request, res: Response) -> CommandResult {
+        let command = req.command();
+        let name = match interacted_subcommand(&req) {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+
+        for defined_command in self.commands.iter() {
+            if name == defined_command.name() {
+                return defined_command
+                    .modal(req.with_command(command.descend(&name)), res)
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the next subcommand in the path of the interacted component or submitted modal.
+fn interacted_subcommand(req: &Request) -> Option<String> {
+    let path = match (req.component(), req.submission()) {
+        (Some(component), _) => component.path(),
+        (_, Some(submission)) => submission.path(),
+        _ => return None,
+    };
+    path.get(req.command().path().len()).cloned()
+}
+
+/// Turns the layout of a command into an option of its parent.
+fn to_subcommand_option(command: &dyn Command) -> CreateApplicationCommandOption {
+    let mut layout = CreateApplicationCommand::default();
+    command.layout(&mut layout);
+    let options = layout
+        .0
+        .remove("options")
+        .unwrap_or(Value::Array(Vec::new()));
+
+    let is_group = match &options {
+        Value::Array(options) => options
+            .iter()
+            .any(|option| option["type"].as_u64() == Some(CommandOptionType::SubCommand.num())),
+        _ => false,
+    };
+
+    let mut option = CreateApplicationCommandOption::default();
+    option
+        .name(command.name())
+        .description(command.description())
+        .kind(if is_group {
+            CommandOptionType::SubCommandGroup
+        } else {
+            CommandOptionType::SubCommand
+        });
+    option.0.insert("options", options);
+    for key in ["name_localizations", "description_localizations"] {
+        if let Some(localizations) = layout.0.remove(key) {
+            option.0.insert(key, localizations);
+        }
+    }
+    option
+}
@@ -0,0 +1,420 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::sync::{Arc, Weak};
+
+use serenity::{
+    builder::CreateApplicationCommand,
+    json::Value,
+    model::application::command::{CommandOptionType, CommandType},
+};
+
+use super::{
+    autocomplete::MAX_CHOICES, checks, component::ButtonStyle, parser, ActionRow, Button, Choice,
+    Colour, Command, CommandRegistry, CommandResult, Embed, Error, FluentArgs, Message, Request,
+    Response,
+};
+
+/// Number of commands shown on a page of the overview.
+const PAGE_SIZE: usize = 10;
+
+/// Built-in command listing every command the user can use, or showing details of one of them.\
+/// Commands which are disabled, restricted to other guilds or fail their checks are hidden.
+pub struct Help {
+    registry: Weak<CommandRegistry>,
+}
+impl Help {
+    /// Creates the command for the registry it's going to be added to,
+    /// it only holds a weak reference so the registry can be dropped.
+    pub fn new(registry: &Arc<CommandRegistry>) -> Self {
+        Self {
+            registry: Arc::downgrade(registry),
+        }
+    }
+
+    fn registry(&self) -> Option<Arc<CommandRegistry>> {
+        self.registry.upgrade()
+    }
+
+    /// Returns commands the user can use, ordered by category and name.
+    async fn visible(&self, req: &Request) -> Result<Vec<Arc<dyn Command>>, Error> {
+        let registry = self.registry().ok_or_else(dropped)?;
+        let mut commands = Vec::new();
+        for command in registry.commands() {
+            if is_visible(&registry, command.as_ref(), req).await {
+                commands.push(command);
+            }
+        }
+        // Uncategorized commands go first, `sort_by_key` keeps the order of names within categories
+        commands.sort_by_key(|command| registry.category(command.name()));
+        Ok(commands)
+    }
+
+    /// Builds a page of the overview, `page` is clamped to the last one.
+    async fn overview(&self, req: &Request, res: &Response, page: usize) -> Result<Message, Error> {
+        let registry = self.registry().ok_or_else(dropped)?;
+        let commands = self.visible(req).await?;
+        if commands.is_empty() {
+            return Ok(Message::new(res.render("help-empty", None)));
+        }
+        let pages = commands.len().div_ceil(PAGE_SIZE);
+        let page = page.min(pages - 1);
+
+        let mut embed = Embed::new()
+            .title(res.render("help-title", None))
+            .colour(Colour::BLURPLE);
+        let mut fields: Vec<(String, Vec<String>)> = Vec::new();
+        for command in commands.iter().skip(page * PAGE_SIZE).take(PAGE_SIZE) {
+            let category = registry
+                .category(command.name())
+                .unwrap_or_else(|| res.render("help-uncategorized", None));
+            let line = format!(
+                "`{}` - {}",
+                invocation(command.as_ref(), req),
+                summary(command.as_ref(), req, res)
+            );
+            match fields.last_mut() {
+                Some((last, lines)) if *last == category => lines.push(line),
+                _ => fields.push((category, vec![line])),
+            }
+        }
+        for (category, lines) in fields {
+            embed = embed.field(category, lines.join("\n"), false);
+        }
+
+        let mut message = Message::new("");
+        if pages > 1 {
+            let mut args = FluentArgs::new();
+            args.set("page", page + 1);
+            args.set("pages", pages);
+            embed = embed.footer(res.render("help-page", Some(&args)));
+            // Custom IDs of both buttons differ even when they're disabled, as Discord requires
+            message = message.action_row(
+                ActionRow::new()
+                    .button(
+                        Button::new(
+                            req.custom_id(&format!("page-{}", page.saturating_sub(1))),
+                            ButtonStyle::Secondary,
+                        )
+                        .label(res.render("help-previous", None))
+                        .disabled(page == 0),
+                    )
+                    .button(
+                        Button::new(
+                            req.custom_id(&format!("page-{}", page + 1)),
+                            ButtonStyle::Secondary,
+                        )
+                        .label(res.render("help-next", None))
+                        .disabled(page + 1 >= pages),
+                    ),
+            );
+        }
+        Ok(message.embed(embed))
+    }
+
+    /// Builds details of a command, or explains that there is no such command.
+    async fn detail(&self, req: &Request, res: &Response, name: &str) -> Result<Message, Error> {
+        let registry = self.registry().ok_or_else(dropped)?;
+        let command = match registry.get(name) {
+            Some(command) if is_visible(&registry, command.as_ref(), req).await => command,
+            _ => {
+                let mut args = FluentArgs::new();
+                args.set("name", name.to_string());
+                return Ok(Message::new(res.render("help-unknown", Some(&args))));
+            }
+        };
+        let mut layout = CreateApplicationCommand::default();
+        command.layout(&mut layout);
+        let options = match layout.0.get("options") {
+            Some(Value::Array(options)) => options.clone(),
+            _ => Vec::new(),
+        };
+
+        let mut embed = Embed::new()
+            .title(invocation(command.as_ref(), req))
+            .description(summary(command.as_ref(), req, res))
+            .colour(Colour::BLURPLE);
+        match command.kind() {
+            CommandType::User => {
+                embed = embed.field(
+                    res.render("help-usage", None),
+                    res.render("help-context-user", None),
+                    false,
+                )
+            }
+            CommandType::Message => {
+                embed = embed.field(
+                    res.render("help-usage", None),
+                    res.render("help-context-message", None),
+                    false,
+                )
+            }
+            _ if options.iter().any(parser::is_subcommand) => {
+                let subcommands: Vec<String> =
+                    subcommands(&invocation(command.as_ref(), req), &options)
+                        .into_iter()
+                        .map(|(path, description)| format!("`{}` - {}", path, description))
+                        .collect();
+                embed = embed.field(
+                    res.render("help-subcommands", None),
+                    subcommands.join("\n"),
+                    false,
+                );
+            }
+            _ => {
+                let usage = usage(command.as_ref(), &options, req, res);
+                embed = embed.field(res.render("help-usage", None), usage, false);
+            }
+        }
+
+        let aliases = command.aliases();
+        if !aliases.is_empty() {
+            embed = embed.field(
+                res.render("help-aliases", None),
+                aliases
+                    .iter()
+                    .map(|alias| format!("`{}`", alias))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                true,
+            );
+        }
+        if let Some(category) = registry.category(command.name()) {
+            embed = embed.field(res.render("help-category", None), category, true);
+        }
+        let permissions = command.user_permissions();
+        if !permissions.is_empty() {
+            embed = embed.field(
+                res.render("help-permissions", None),
+                permissions.get_permission_names().join(", "),
+                true,
+            );
+        }
+        if let Some(cooldown) = command.cooldown() {
+            let mut args = FluentArgs::new();
+            args.set("uses", cooldown.get_uses());
+            args.set("seconds", cooldown.get_per().as_secs());
+            embed = embed.field(
+                res.render("help-cooldown", None),
+                res.render("help-cooldown-value", Some(&args)),
+                true,
+            );
+        }
+        Ok(Message::from(embed))
+    }
+}
+
+#[async_trait]
+impl Command for Help {
+    fn name<'s>(&self) -> &'s str {
+        "help"
+    }
+    fn description<'s>(&self) -> &'s str {
+        "Show commands you can use and how to use them"
+    }
+
+    fn layout<'s>(
+        &self,
+        command: &'s mut CreateApplicationCommand,
+    ) -> &'s mut CreateApplicationCommand {
+        self.default_layout(command).create_option(|option| {
+            option
+                .name("command")
+                .description("Command to show details of")
+                .kind(CommandOptionType::String)
+                .set_autocomplete(true)
+        })
+    }
+
+    fn ephemeral(&self) -> bool {
+        true
+    }
+
+    async fn run(&self, req: Request, res: Response) -> CommandResult {
+        let message = match req.command().get_optional::<String>("command")? {
+            Some(name) => self.detail(&req, &res, &name).await?,
+            None => self.overview(&req, &res, 0).await?,
+        };
+        res.send_ephemeral_message(message, true).await?;
+        Ok(())
+    }
+
+    async fn autocomplete(&self, _req: Request, _option: &str, value: &str) -> Vec<Choice> {
+        // Checks may hit the API, which is too slow to do on every keystroke
+        let registry = match self.registry() {
+            Some(registry) => registry,
+            None => return Vec::new(),
+        };
+        registry
+            .commands()
+            .into_iter()
+            .filter(|command| {
+                command.kind() == CommandType::ChatInput
+                    && registry.is_enabled(command.name())
+                    && command.name().starts_with(value)
+            })
+            .take(MAX_CHOICES)
+            .map(|command| Choice::string(command.name(), command.name()))
+            .collect()
+    }
+
+    async fn component(&self, req: Request, res: Response) -> CommandResult {
+        let page = req
+            .component()
+            .and_then(|component| {
+                component
+                    .id()
+                    .strip_prefix("page-")
+                    .and_then(|page| page.parse::<usize>().ok())
+            })
+            .unwrap_or_default();
+        let message = self.overview(&req, &res, page).await?;
+        res.update_message(message).await?;
+        Ok(())
+    }
+}
+
+fn dropped() -> Error {
+    Error::internal("command registry has been dropped")
+}
+
+/// Whether the user can use the command where the request was made.
+async fn is_visible(registry: &CommandRegistry, command: &dyn Command, req: &Request) -> bool {
+    let guilds = command.guilds();
+    registry.is_enabled(command.name())
+        && (guilds.is_empty() || req.guild.is_some_and(|guild| guilds.contains(&guild)))
+        && checks::evaluate(command, req).await.is_ok()
+}
+
+/// How the command is invoked, `/name` for slash commands, the prefix and the name when help
+/// is invoked as a message command, and the bare name for context menu ones.
+fn invocation(command: &dyn Command, req: &Request) -> String {
+    match command.kind() {
+        CommandType::ChatInput => {
+            format!("{}{}", req.prefix.as_deref().unwrap_or("/"), command.name())
+        }
+        _ => command.name().to_string(),
+    }
+}
+
+/// Invocation of the command with its options, followed by descriptions of the options.
+fn usage(command: &dyn Command, options: &[Value], req: &Request, res: &Response) -> String {
+    let mut usage = format!("`{}", invocation(command, req));
+    let mut descriptions = Vec::new();
+    for option in options {
+        let name = option["name"].as_str().unwrap_or_default();
+        let kind = res.render(parser::kind_key(parser::option_kind(option)), None);
+        // Message commands take arguments in order, without their names
+        let argument = match req.prefix {
+            Some(_) => format!("<{}>", name),
+            None => format!("{}:<{}>", name, kind),
+        };
+        if option["required"].as_bool().unwrap_or(false) {
+            usage.push_str(&format!(" {}", argument));
+        } else {
+            usage.push_str(&format!(" [{}]", argument));
+        }
+        descriptions.push(format!(
+            "`{}` - {}",
+            name,
+            option["description"].as_str().unwrap_or_default()
+        ));
+    }
+    usage.push('`');
+    if !descriptions.is_empty() {
+        usage = format!("{}\n{}", usage, descriptions.join("\n"));
+    }
+    usage
+}
+
+/// Description of the command in the locale of the user, context menu commands have none.
+fn summary(command: &dyn Command, req: &Request, res: &Response) -> String {
+    match command.kind() {
+        CommandType::User => return res.render("help-context-user", None),
+        CommandType::Message => return res.render("help-context-message", None),
+        _ => {}
+    }
+    req.locale
+        .as_deref()
+        .and_then(|locale| {
+            command
+                .localized_descriptions()
+                .into_iter()
+                .find(|(localized, _)| *localized == locale)
+        })
+        .map(|(_, description)| description)
+        .unwrap_or_else(|| command.description())
+        .to_string()
+}
+
+/// Lists invocations and descriptions of subcommands, descending into subcommand groups.
+fn subcommands(path: &str, options: &[Value]) -> Vec<(String, String)> {
+    let mut subcommands = Vec::new();
+    for option in options {
+        let path = format!("{} {}", path, option["name"].as_str().unwrap_or_default());
+        match option["type"].as_u64() {
+            Some(kind) if kind == CommandOptionType::SubCommandGroup.num() => {
+                if let Value::Array(options) = &option["options"] {
+                    subcommands.extend(self::subcommands(&path, options));
+                }
+            }
+            Some(kind) if kind == CommandOptionType::SubCommand.num() => subcommands.push((
+                path,
+                option["description"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            )),
+            _ => {}
+        }
+    }
+    subcommands
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::json::json;
+
+    use super::*;
+    use crate::commands::framework::{Origin, Recorder};
+
+    fn options() -> Vec<Value> {
+        vec![
+            json!({"type": 4, "name": "number", "description": "Number", "required": true}),
+            json!({"type": 6, "name": "user", "description": "User"}),
+        ]
+    }
+
+    #[test]
+    fn slash_usage_names_options() {
+        let registry = Arc::new(CommandRegistry::new());
+        let req = Request::builder("help").build();
+        let res = Response::from_sink(Arc::new(Recorder::new()), Origin::InteractionCommand);
+
+        let usage = usage(&Help::new(&registry), &options(), &req, &res);
+        assert!(usage.starts_with("`/help number:<integer> [user:<user>]`\n"));
+    }
+
+    #[test]
+    fn message_usage_uses_prefix_and_positions() {
+        let registry = Arc::new(CommandRegistry::new());
+        let req = Request::builder("help").prefix("!").build();
+        let res = Response::from_sink(Arc::new(Recorder::new()), Origin::Message);
+
+        let usage = usage(&Help::new(&registry), &options(), &req, &res);
+        assert!(usage.starts_with("`!help <number> [<user>]`\n"));
+    }
+}
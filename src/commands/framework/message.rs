@@ -14,13 +14,151 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::path::PathBuf;
+
+use serenity::{
+    builder::{
+        CreateAllowedMentions, CreateComponents, CreateInteractionResponseData,
+        CreateInteractionResponseFollowup, CreateMessage, EditInteractionResponse, EditMessage,
+        ParseValue,
+    },
+    model::{
+        application::interaction::MessageFlags as InteractionMessageFlags,
+        channel::{AttachmentType, MessageFlags},
+        id::{RoleId, UserId},
+    },
+};
+
+use super::{component::ActionRow, embed::Embed};
+
+#[derive(Debug, Clone)]
+enum FileSource {
+    Bytes(Vec<u8>),
+    Path(PathBuf),
+}
+
+/// File attached to a message.
+#[derive(Debug, Clone)]
+pub struct File {
+    source: FileSource,
+    filename: String,
+}
+impl File {
+    pub fn from_bytes(data: impl Into<Vec<u8>>, filename: impl ToString) -> Self {
+        Self {
+            source: FileSource::Bytes(data.into()),
+            filename: filename.to_string(),
+        }
+    }
+    /// Reads the file when the message is sent, it's named after the last component of the path.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            filename: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            source: FileSource::Path(path),
+        }
+    }
+
+    pub fn get_filename(&self) -> String {
+        self.filename.clone()
+    }
+
+    fn to_attachment(&self) -> AttachmentType<'_> {
+        match &self.source {
+            FileSource::Bytes(data) => AttachmentType::Bytes {
+                data: data.as_slice().into(),
+                filename: self.filename.clone(),
+            },
+            FileSource::Path(path) => AttachmentType::Path(path),
+        }
+    }
+}
+
+/// Mentions which are allowed to ping, nothing pings by default.
+#[derive(Debug, Clone, Default)]
+pub struct AllowedMentions {
+    everyone: bool,
+    all_users: bool,
+    all_roles: bool,
+    users: Vec<UserId>,
+    roles: Vec<RoleId>,
+    replied_user: bool,
+}
+impl AllowedMentions {
+    pub fn none() -> Self {
+        Self::default()
+    }
+    /// Allows every mention except the author of a replied message.
+    pub fn all() -> Self {
+        Self::default().everyone(true).users(true).roles(true)
+    }
+
+    /// Allows `@everyone` and `@here`.
+    pub fn everyone(mut self, everyone: bool) -> Self {
+        self.everyone = everyone;
+        self
+    }
+    /// Allows every mentioned user, overrides [AllowedMentions::user].
+    pub fn users(mut self, users: bool) -> Self {
+        self.all_users = users;
+        self
+    }
+    /// Allows every mentioned role, overrides [AllowedMentions::role].
+    pub fn roles(mut self, roles: bool) -> Self {
+        self.all_roles = roles;
+        self
+    }
+    pub fn user(mut self, user: impl Into<UserId>) -> Self {
+        self.users.push(user.into());
+        self
+    }
+    pub fn role(mut self, role: impl Into<RoleId>) -> Self {
+        self.roles.push(role.into());
+        self
+    }
+    /// Pings the author of the message a message command is replied to.
+    pub fn replied_user(mut self, replied_user: bool) -> Self {
+        self.replied_user = replied_user;
+        self
+    }
+
+    fn build<'m>(&self, mentions: &'m mut CreateAllowedMentions) -> &'m mut CreateAllowedMentions {
+        mentions.empty_parse();
+        if self.everyone {
+            mentions.parse(ParseValue::Everyone);
+        }
+        if self.all_users {
+            mentions.parse(ParseValue::Users);
+        } else {
+            mentions.users(self.users.iter().copied());
+        }
+        if self.all_roles {
+            mentions.parse(ParseValue::Roles);
+        } else {
+            mentions.roles(self.roles.iter().copied());
+        }
+        mentions.replied_user(self.replied_user)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Message {
     content: String,
+    embeds: Vec<Embed>,
+    files: Vec<File>,
+    components: Vec<ActionRow>,
+    allowed_mentions: Option<AllowedMentions>,
+    tts: bool,
+    suppress_embeds: bool,
 }
 impl Message {
     pub fn new(content: impl ToString) -> Self {
         Self {
             content: content.to_string(),
+            ..Default::default()
         }
     }
 
@@ -31,15 +169,179 @@ impl Message {
     pub fn get_content(&self) -> String {
         self.content.clone()
     }
+
+    /// Adds an embed, up to 10 embeds can be added.
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.embeds.push(embed);
+        self
+    }
+    pub fn get_embeds(&self) -> Vec<Embed> {
+        self.embeds.clone()
+    }
+
+    /// Attaches a file. Files are kept when the message fills in a deferred response,
+    /// but editing the original response of an interaction with them fails.
+    pub fn file(mut self, file: File) -> Self {
+        self.files.push(file);
+        self
+    }
+    pub fn get_files(&self) -> Vec<File> {
+        self.files.clone()
+    }
+
+    /// Adds a row of buttons or a select menu, up to 5 rows can be added.
+    pub fn action_row(mut self, action_row: ActionRow) -> Self {
+        self.components.push(action_row);
+        self
+    }
+    pub fn get_components(&self) -> Vec<ActionRow> {
+        self.components.clone()
+    }
+
+    /// Restricts which mentions ping, Discord's defaults are used if it's not set.\
+    /// Replies to message commands don't ping their author unless allowed.
+    pub fn allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.allowed_mentions = Some(allowed_mentions);
+        self
+    }
+    pub fn get_allowed_mentions(&self) -> Option<AllowedMentions> {
+        self.allowed_mentions.clone()
+    }
+
+    /// Reads the message out loud, it's ignored when editing a message and
+    /// makes editing the original response of an interaction fail.
+    pub fn tts(mut self, tts: bool) -> Self {
+        self.tts = tts;
+        self
+    }
+    pub fn get_tts(&self) -> bool {
+        self.tts
+    }
+
+    /// Hides embeds generated from links in the content,
+    /// can't be set when editing the original response of an interaction.
+    pub fn suppress_embeds(mut self, suppress_embeds: bool) -> Self {
+        self.suppress_embeds = suppress_embeds;
+        self
+    }
+    pub fn get_suppress_embeds(&self) -> bool {
+        self.suppress_embeds
+    }
+
+    /// Checks if the message has files, TTS or suppressed embeds,
+    /// which editing the original response of an interaction can't set.
+    pub(crate) fn has_files_or_flags(&self) -> bool {
+        !self.files.is_empty() || self.tts || self.suppress_embeds
+    }
+
+    fn build_components<'c>(
+        &self,
+        components: &'c mut CreateComponents,
+    ) -> &'c mut CreateComponents {
+        for action_row in self.components.iter() {
+            action_row.build(components);
+        }
+        components
+    }
+
+    pub(crate) fn build_interaction_response<'a, 'b>(
+        &'a self,
+        data: &'b mut CreateInteractionResponseData<'a>,
+    ) -> &'b mut CreateInteractionResponseData<'a> {
+        if !self.content.is_empty() {
+            data.content(&self.content);
+        }
+        if let Some(allowed_mentions) = &self.allowed_mentions {
+            data.allowed_mentions(|mentions| allowed_mentions.build(mentions));
+        }
+        if self.suppress_embeds {
+            data.flags(InteractionMessageFlags::SUPPRESS_EMBEDS);
+        }
+        data.set_embeds(self.embeds.iter().map(Embed::build))
+            .add_files(self.files.iter().map(File::to_attachment))
+            .tts(self.tts)
+            .components(|components| self.build_components(components))
+    }
+    pub(crate) fn build_followup<'a, 'b>(
+        &'a self,
+        followup: &'b mut CreateInteractionResponseFollowup<'a>,
+    ) -> &'b mut CreateInteractionResponseFollowup<'a> {
+        if !self.content.is_empty() {
+            followup.content(&self.content);
+        }
+        if let Some(allowed_mentions) = &self.allowed_mentions {
+            followup.allowed_mentions(|mentions| allowed_mentions.build(mentions));
+        }
+        if self.suppress_embeds {
+            followup.flags(InteractionMessageFlags::SUPPRESS_EMBEDS);
+        }
+        followup
+            .set_embeds(self.embeds.iter().map(Embed::build))
+            .add_files(self.files.iter().map(File::to_attachment))
+            .tts(self.tts)
+            .components(|components| self.build_components(components))
+    }
+    pub(crate) fn build_message<'a, 'b>(
+        &'a self,
+        message: &'b mut CreateMessage<'a>,
+    ) -> &'b mut CreateMessage<'a> {
+        if !self.content.is_empty() {
+            message.content(&self.content);
+        }
+        if let Some(allowed_mentions) = &self.allowed_mentions {
+            message.allowed_mentions(|mentions| allowed_mentions.build(mentions));
+        }
+        if self.suppress_embeds {
+            message.flags(MessageFlags::SUPPRESS_EMBEDS);
+        }
+        message
+            .set_embeds(self.embeds.iter().map(Embed::build).collect())
+            .add_files(self.files.iter().map(File::to_attachment))
+            .tts(self.tts)
+            .components(|components| self.build_components(components))
+    }
+    pub(crate) fn build_edit_response<'b>(
+        &self,
+        response: &'b mut EditInteractionResponse,
+    ) -> &'b mut EditInteractionResponse {
+        if let Some(allowed_mentions) = &self.allowed_mentions {
+            response.allowed_mentions(|mentions| allowed_mentions.build(mentions));
+        }
+        response
+            .content(&self.content)
+            .set_embeds(self.embeds.iter().map(Embed::build).collect())
+            .components(|components| self.build_components(components))
+    }
+    pub(crate) fn build_edit_message<'a, 'b>(
+        &'a self,
+        message: &'b mut EditMessage<'a>,
+    ) -> &'b mut EditMessage<'a> {
+        if let Some(allowed_mentions) = &self.allowed_mentions {
+            message.allowed_mentions(|mentions| allowed_mentions.build(mentions));
+        }
+        for file in self.files.iter() {
+            message.attachment(file.to_attachment());
+        }
+        message
+            .content(&self.content)
+            .set_embeds(self.embeds.iter().map(Embed::build).collect())
+            .suppress_embeds(self.suppress_embeds)
+            .components(|components| self.build_components(components))
+    }
 }
 
-impl<'msg> Into<Message> for &'msg str {
-    fn into(self) -> Message {
-        Message::new(self)
+impl From<&str> for Message {
+    fn from(content: &str) -> Self {
+        Message::new(content)
+    }
+}
+impl From<String> for Message {
+    fn from(content: String) -> Self {
+        Message::new(content)
     }
 }
-impl Into<Message> for String {
-    fn into(self) -> Message {
-        Message::new(self)
+impl From<Embed> for Message {
+    fn from(embed: Embed) -> Self {
+        Message::default().embed(embed)
     }
 }
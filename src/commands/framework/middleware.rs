@@ -0,0 +1,43 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::time::Duration;
+
+use super::{Command, CommandResult, Request};
+
+/// Hooks ran around every command, e.g. for audit logging, metrics or blacklists.
+#[async_trait]
+pub trait Middleware: Send + Sync + 'static {
+    /// Asynchronous method which will be ran before the command is checked and ran.\
+    /// Returning an error stops the command and reports the error instead,
+    /// [Error::Cancelled](super::Error::Cancelled) stops it without telling the user.
+    /// Options of message commands aren't parsed yet, so they're empty.
+    async fn before(&self, _command: &dyn Command, _req: &Request) -> CommandResult {
+        Ok(())
+    }
+
+    /// Asynchronous method which will be ran after the command finished,
+    /// receives its outcome and how long checking and running it took.\
+    /// Isn't ran if any middleware stopped the command beforehand.
+    async fn after(
+        &self,
+        _command: &dyn Command,
+        _req: &Request,
+        _result: &CommandResult,
+        _elapsed: Duration,
+    ) {
+    }
+}
@@ -14,12 +14,44 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+pub mod arguments;
+pub mod autocomplete;
+pub mod catalog;
+pub mod checks;
 pub mod command;
+pub mod component;
+pub mod cooldown;
+pub mod embed;
+pub mod error;
+pub mod group;
+pub mod help;
 pub mod message;
+pub mod middleware;
+pub mod modal;
+pub mod parser;
+pub mod recorder;
+pub mod registry;
 pub mod request;
 pub mod response;
+pub mod state;
 
+pub use arguments::{ArgumentError, FromOption};
+pub use autocomplete::Choice;
+pub use catalog::Catalog;
 pub use command::Command;
-pub use message::Message;
-pub use request::Request;
-pub use response::Response;
+pub use component::{ActionRow, Button, ComponentHandler, SelectMenu, SelectOption};
+pub use cooldown::Cooldown;
+pub use embed::{Colour, Embed};
+pub use error::{CommandResult, DefaultErrorHandler, Error, ErrorHandler};
+pub use eternacore_macros::command;
+pub use fluent_bundle::FluentArgs;
+pub use group::Group;
+pub use help::Help;
+pub use message::{AllowedMentions, File, Message};
+pub use middleware::Middleware;
+pub use modal::{FromSubmission, InputTextStyle, Modal, TextInput};
+pub use recorder::{Recorded, Recorder};
+pub use registry::CommandRegistry;
+pub use request::{MemberView, Request, RequestBuilder};
+pub use response::{Origin, Response, Sent, SentMessage, Sink};
+pub use state::State;
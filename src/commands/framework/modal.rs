@@ -0,0 +1,231 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{collections::HashMap, str::FromStr};
+
+use serenity::{
+    builder::CreateInteractionResponseData,
+    model::application::component::{ActionRow as SerenityActionRow, ActionRowComponent},
+};
+
+pub use serenity::model::application::component::InputTextStyle;
+
+use super::{ArgumentError, FromOption};
+
+#[derive(Debug, Clone)]
+pub struct TextInput {
+    custom_id: String,
+    label: String,
+    style: InputTextStyle,
+    placeholder: Option<String>,
+    value: Option<String>,
+    min_length: Option<u64>,
+    max_length: Option<u64>,
+    required: bool,
+}
+impl TextInput {
+    /// Creates a required text input, its value is retrieved by `custom_id` after submission.
+    pub fn new(custom_id: impl ToString, label: impl ToString, style: InputTextStyle) -> Self {
+        Self {
+            custom_id: custom_id.to_string(),
+            label: label.to_string(),
+            style,
+            placeholder: None,
+            value: None,
+            min_length: None,
+            max_length: None,
+            required: true,
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl ToString) -> Self {
+        self.placeholder = Some(placeholder.to_string());
+        self
+    }
+    /// Pre-fills the input.
+    pub fn value(mut self, value: impl ToString) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+    pub fn length(mut self, min: u64, max: u64) -> Self {
+        self.min_length = Some(min);
+        self.max_length = Some(max);
+        self
+    }
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+}
+
+/// Form with up to 5 text inputs.
+#[derive(Debug, Clone)]
+pub struct Modal {
+    custom_id: String,
+    title: String,
+    inputs: Vec<TextInput>,
+}
+impl Modal {
+    /// Creates a modal, `custom_id` has to be namespaced like custom IDs of components.
+    pub fn new(custom_id: impl ToString, title: impl ToString) -> Self {
+        Self {
+            custom_id: custom_id.to_string(),
+            title: title.to_string(),
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn input(mut self, input: TextInput) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    pub(crate) fn build<'a, 'b>(
+        &self,
+        data: &'b mut CreateInteractionResponseData<'a>,
+    ) -> &'b mut CreateInteractionResponseData<'a> {
+        data.custom_id(&self.custom_id)
+            .title(&self.title)
+            .components(|components| {
+                for input in self.inputs.iter() {
+                    components.create_action_row(|row| {
+                        row.create_input_text(|text| {
+                            text.custom_id(&input.custom_id)
+                                .label(&input.label)
+                                .style(input.style)
+                                .required(input.required);
+                            if let Some(placeholder) = &input.placeholder {
+                                text.placeholder(placeholder);
+                            }
+                            if let Some(value) = &input.value {
+                                text.value(value);
+                            }
+                            if let Some(min_length) = input.min_length {
+                                text.min_length(min_length);
+                            }
+                            if let Some(max_length) = input.max_length {
+                                text.max_length(max_length);
+                            }
+                            text
+                        })
+                    });
+                }
+                components
+            })
+    }
+}
+
+/// Values of a submitted modal.
+#[derive(Debug, Clone)]
+pub struct Submission {
+    id: String,
+    path: Vec<String>,
+    fields: HashMap<String, String>,
+}
+impl Submission {
+    pub(crate) fn new(id: String, path: Vec<String>, rows: &[SerenityActionRow]) -> Self {
+        let fields = rows
+            .iter()
+            .flat_map(|row| row.components.iter())
+            .filter_map(|component| match component {
+                ActionRowComponent::InputText(input) => {
+                    Some((input.custom_id.clone(), input.value.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        Self { id, path, fields }
+    }
+
+    /// Custom ID of the modal without its namespace.
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    /// Path of the command owning the modal, taken from the namespace of its custom ID.
+    pub fn path(&self) -> Vec<String> {
+        self.path.clone()
+    }
+
+    /// Builds a value out of the whole submission.
+    pub fn parse<T: FromSubmission>(&self) -> Result<T, ArgumentError> {
+        T::from_submission(self)
+    }
+
+    /// Extracts the value of a text input, failing if it's missing, empty or can't be parsed.\
+    /// Types are limited to options ones, so errors name the expected type from the catalog.
+    pub fn get<T: FromStr + FromOption>(&self, custom_id: &str) -> Result<T, ArgumentError> {
+        self.get_optional(custom_id)?
+            .ok_or_else(|| ArgumentError::Missing(custom_id.to_string()))
+    }
+
+    /// Extracts the value of an optional text input, empty inputs are treated as missing.
+    pub fn get_optional<T: FromStr + FromOption>(
+        &self,
+        custom_id: &str,
+    ) -> Result<Option<T>, ArgumentError> {
+        match self.fields.get(custom_id) {
+            Some(value) if !value.is_empty() => {
+                value
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| ArgumentError::Mistyped {
+                        name: custom_id.to_string(),
+                        expected: T::EXPECTED,
+                    })
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Type which can be built from the values of a submitted modal.
+pub trait FromSubmission: Sized {
+    fn from_submission(submission: &Submission) -> Result<Self, ArgumentError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::json::{self, json};
+
+    use super::*;
+
+    fn submission(value: &str) -> Submission {
+        let rows: Vec<SerenityActionRow> = json::prelude::from_value(json!([{
+            "type": 1,
+            "components": [{ "type": 4, "custom_id": "hours", "value": value }],
+        }]))
+        .unwrap();
+        Submission::new(String::from("remind"), Vec::new(), &rows)
+    }
+
+    #[test]
+    fn treats_empty_inputs_as_missing() {
+        assert_eq!(submission("").get_optional::<i64>("hours").unwrap(), None);
+        assert_eq!(submission("3").get::<i64>("hours").unwrap(), 3);
+        assert!(matches!(
+            submission("").get::<i64>("hours"),
+            Err(ArgumentError::Missing(_))
+        ));
+    }
+
+    #[test]
+    fn renders_mistyped_values_from_the_catalog() {
+        let why = submission("soon").get::<i64>("hours").unwrap_err();
+        assert_eq!(why.to_string(), "Argument `hours` must be a integer.");
+    }
+}
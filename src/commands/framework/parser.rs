@@ -0,0 +1,703 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::fmt;
+
+use fluent_bundle::FluentArgs;
+use serenity::{
+    builder::CreateApplicationCommand,
+    json::{self, Value},
+    model::{
+        channel::Message as SerenityMessage,
+        prelude::{
+            command::CommandOptionType,
+            interaction::application_command::{CommandDataOption, CommandDataOptionValue},
+            ChannelId, RoleId, UserId,
+        },
+    },
+    prelude::Context,
+    utils::{parse_channel, parse_role, parse_username},
+};
+
+use super::Catalog;
+
+/// Longest text Discord accepts for an option.
+const MAX_LENGTH: u64 = 6000;
+
+/// Reasons why the text of a message command doesn't match the layout of the command.
+#[derive(Debug)]
+pub enum ParseError {
+    /// Required option wasn't given.
+    MissingOption(String),
+    /// Option was given, but its value can't be read as the declared type.
+    InvalidOption {
+        name: String,
+        kind: CommandOptionType,
+        value: String,
+    },
+    /// Numeric option is outside of the declared bounds.
+    OutOfRange { name: String, value: String },
+    /// Text option is shorter or longer than declared.
+    InvalidLength { name: String, min: u64, max: u64 },
+    /// Option has choices, but the value is none of them.
+    NotAChoice {
+        name: String,
+        value: String,
+        choices: Vec<String>,
+    },
+    /// Command has subcommands, but none was given.
+    MissingSubcommand(Vec<String>),
+    /// Given subcommand doesn't exist.
+    UnknownSubcommand(String),
+    /// There's more text than the command accepts.
+    TrailingInput(String),
+}
+impl ParseError {
+    /// Renders the explanation shown to the user, `render` looks up messages in a catalog.
+    pub fn render(&self, render: &dyn Fn(&str, Option<&FluentArgs>) -> String) -> String {
+        let mut args = FluentArgs::new();
+        let key = match self {
+            ParseError::MissingOption(name) => {
+                args.set("name", name.clone());
+                "argument-missing"
+            }
+            ParseError::InvalidOption { name, kind, value } => {
+                args.set("name", name.clone());
+                args.set("kind", render(kind_key(*kind), None));
+                args.set("value", value.clone());
+                "argument-invalid"
+            }
+            ParseError::OutOfRange { name, value } => {
+                args.set("name", name.clone());
+                args.set("value", value.clone());
+                "argument-out-of-range"
+            }
+            ParseError::InvalidLength { name, min, max } => {
+                args.set("name", name.clone());
+                args.set("min", *min);
+                args.set("max", *max);
+                "argument-invalid-length"
+            }
+            ParseError::NotAChoice {
+                name,
+                value,
+                choices,
+            } => {
+                args.set("name", name.clone());
+                args.set("value", value.clone());
+                args.set("choices", choices.join("`, `"));
+                "argument-not-a-choice"
+            }
+            ParseError::MissingSubcommand(expected) => {
+                args.set("expected", expected.join("`, `"));
+                "argument-missing-subcommand"
+            }
+            ParseError::UnknownSubcommand(name) => {
+                args.set("name", name.clone());
+                "argument-unknown-subcommand"
+            }
+            ParseError::TrailingInput(input) => {
+                args.set("input", input.clone());
+                "argument-trailing"
+            }
+        };
+        render(key, Some(&args))
+    }
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&|key, args| Catalog::built_in().render(None, key, args)))
+    }
+}
+impl std::error::Error for ParseError {}
+
+/// Returns the catalog key of the human readable name of an option type, e.g. `text` for strings.
+pub fn kind_key(kind: CommandOptionType) -> &'static str {
+    match kind {
+        CommandOptionType::String => "option-kind-string",
+        CommandOptionType::Integer => "option-kind-integer",
+        CommandOptionType::Boolean => "option-kind-boolean",
+        CommandOptionType::User => "option-kind-user",
+        CommandOptionType::Channel => "option-kind-channel",
+        CommandOptionType::Role => "option-kind-role",
+        CommandOptionType::Mentionable => "option-kind-mentionable",
+        CommandOptionType::Number => "option-kind-number",
+        CommandOptionType::Attachment => "option-kind-attachment",
+        _ => "option-kind-value",
+    }
+}
+
+/// Splits input into whitespace separated arguments, keeping `"quoted text"` together.
+struct Arguments<'a> {
+    input: &'a str,
+}
+impl<'a> Arguments<'a> {
+    fn next_argument(&mut self) -> Option<&'a str> {
+        let input = self.input.trim_start();
+        if input.is_empty() {
+            self.input = input;
+            return None;
+        }
+
+        if let Some(quoted) = input.strip_prefix('"') {
+            if let Some(end) = quoted.find('"') {
+                self.input = &quoted[end + 1..];
+                return Some(&quoted[..end]);
+            }
+        }
+        let end = input.find(char::is_whitespace).unwrap_or(input.len());
+        self.input = &input[end..];
+        Some(&input[..end])
+    }
+
+    fn rest(&mut self) -> Option<&'a str> {
+        let input = self.input.trim();
+        self.input = "";
+        if input.is_empty() {
+            None
+        } else {
+            Some(input)
+        }
+    }
+}
+
+/// Looks up users, roles, channels and attachments arguments refer to.
+#[async_trait]
+trait Resolver: Sync {
+    async fn user(&self, argument: &str) -> Option<CommandDataOptionValue>;
+    async fn role(&self, argument: &str) -> Option<CommandDataOptionValue>;
+    async fn channel(&self, argument: &str) -> Option<CommandDataOptionValue>;
+    /// Returns the attachment at `index`, attachments aren't given as text.
+    fn attachment(&self, index: usize) -> Option<CommandDataOptionValue>;
+}
+
+/// Resolves arguments of a message command through Discord.
+struct MessageResolver<'a> {
+    context: &'a Context,
+    message: &'a SerenityMessage,
+}
+#[async_trait]
+impl Resolver for MessageResolver<'_> {
+    async fn user(&self, argument: &str) -> Option<CommandDataOptionValue> {
+        let id = UserId(parse_username(argument).or_else(|| argument.parse().ok())?);
+        let user = match self.message.mentions.iter().find(|user| user.id == id) {
+            Some(user) => user.clone(),
+            None => id.to_user(self.context).await.ok()?,
+        };
+        Some(CommandDataOptionValue::User(user, None))
+    }
+
+    async fn role(&self, argument: &str) -> Option<CommandDataOptionValue> {
+        let id = RoleId(parse_role(argument).or_else(|| argument.parse().ok())?);
+        let mut roles = self
+            .message
+            .guild_id?
+            .roles(&self.context.http)
+            .await
+            .ok()?;
+        Some(CommandDataOptionValue::Role(roles.remove(&id)?))
+    }
+
+    async fn channel(&self, argument: &str) -> Option<CommandDataOptionValue> {
+        let id = parse_channel(argument).or_else(|| argument.parse().ok())?;
+        let channel = ChannelId(id).to_channel(self.context).await.ok()?;
+        Some(CommandDataOptionValue::Channel(
+            json::prelude::from_value(json::prelude::to_value(channel).ok()?).ok()?,
+        ))
+    }
+
+    fn attachment(&self, index: usize) -> Option<CommandDataOptionValue> {
+        self.message
+            .attachments
+            .get(index)
+            .map(|attachment| CommandDataOptionValue::Attachment(attachment.clone()))
+    }
+}
+
+/// Parses the text following the command name into options declared by the command's `layout`.
+///
+/// Subcommand groups and subcommands are selected by name first,
+/// arguments are positional and follow the order of declared options, the last text option
+/// takes everything that's left. Users, channels and roles can be given either as mentions or IDs,
+/// attachments are taken from the message itself.
+pub async fn parse_options(
+    context: &Context,
+    message: &SerenityMessage,
+    layout: &CreateApplicationCommand,
+    input: &str,
+) -> Result<Vec<CommandDataOption>, ParseError> {
+    let declared = match layout.0.get("options") {
+        Some(Value::Array(declared)) => declared.as_slice(),
+        _ => &[],
+    };
+    parse(&MessageResolver { context, message }, declared, input).await
+}
+
+async fn parse(
+    resolver: &impl Resolver,
+    mut declared: &[Value],
+    input: &str,
+) -> Result<Vec<CommandDataOption>, ParseError> {
+    let mut arguments = Arguments { input };
+    let mut attachments = 0;
+    let mut options = Vec::new();
+
+    // Descend into subcommand groups and subcommands, they can't be mixed with other options
+    let mut path = Vec::new();
+    while declared.iter().any(is_subcommand) {
+        let argument = arguments.next_argument().ok_or_else(|| {
+            ParseError::MissingSubcommand(
+                declared
+                    .iter()
+                    .filter_map(|option| option["name"].as_str().map(str::to_string))
+                    .collect(),
+            )
+        })?;
+        let subcommand = declared
+            .iter()
+            .find(|option| option["name"].as_str() == Some(argument))
+            .ok_or_else(|| ParseError::UnknownSubcommand(argument.to_string()))?;
+
+        path.push(subcommand);
+        declared = match &subcommand["options"] {
+            Value::Array(declared) => declared.as_slice(),
+            _ => &[],
+        };
+    }
+
+    for (index, option) in declared.iter().enumerate() {
+        let name = option["name"].as_str().unwrap_or_default().to_string();
+        let kind = option_kind(option);
+        let required = option["required"].as_bool().unwrap_or(false);
+
+        let resolved = if kind == CommandOptionType::Attachment {
+            attachments += 1;
+            resolver.attachment(attachments - 1)
+        } else {
+            let argument = if kind == CommandOptionType::String && index == declared.len() - 1 {
+                arguments.rest()
+            } else {
+                arguments.next_argument()
+            };
+            match argument {
+                Some(argument) => Some(
+                    resolve(resolver, option, &name, kind, argument)
+                        .await
+                        .ok_or_else(|| ParseError::InvalidOption {
+                            name: name.clone(),
+                            kind,
+                            value: argument.to_string(),
+                        })??,
+                ),
+                None => None,
+            }
+        };
+
+        match resolved {
+            Some(resolved) => options.push(to_option(name, kind, resolved)),
+            None if required => return Err(ParseError::MissingOption(name)),
+            None => {}
+        }
+    }
+
+    if let Some(rest) = arguments.rest() {
+        return Err(ParseError::TrailingInput(rest.to_string()));
+    }
+    for subcommand in path.into_iter().rev() {
+        let mut option: CommandDataOption = json::prelude::from_value(json::json!({
+            "name": subcommand["name"],
+            "type": subcommand["type"],
+        }))
+        .expect("option built from valid JSON");
+        option.options = options;
+        options = vec![option];
+    }
+    Ok(options)
+}
+
+/// Reads the type of an option declared in a layout.
+pub(crate) fn option_kind(option: &Value) -> CommandOptionType {
+    json::prelude::from_value(option["type"].clone()).unwrap_or(CommandOptionType::Unknown)
+}
+
+/// Checks if a declared option is a subcommand or a subcommand group.
+pub(crate) fn is_subcommand(option: &Value) -> bool {
+    let kind = option["type"].as_u64();
+    kind == Some(CommandOptionType::SubCommand.num())
+        || kind == Some(CommandOptionType::SubCommandGroup.num())
+}
+
+/// Resolves a single argument, returns `None` if it can't be read as `kind`.\
+/// Arguments of options with choices have to match the name or the value of one of them.
+async fn resolve(
+    resolver: &impl Resolver,
+    option: &Value,
+    name: &str,
+    kind: CommandOptionType,
+    argument: &str,
+) -> Option<Result<CommandDataOptionValue, ParseError>> {
+    let chosen;
+    let argument = match option["choices"].as_array() {
+        Some(choices) if !choices.is_empty() => {
+            let choice = choices.iter().find(|choice| {
+                choice["name"]
+                    .as_str()
+                    .is_some_and(|choice| choice.eq_ignore_ascii_case(argument))
+                    || choice_value(&choice["value"]) == argument
+            });
+            match choice {
+                Some(choice) => {
+                    chosen = choice_value(&choice["value"]);
+                    chosen.as_str()
+                }
+                None => {
+                    return Some(Err(ParseError::NotAChoice {
+                        name: name.to_string(),
+                        value: argument.to_string(),
+                        choices: choices
+                            .iter()
+                            .filter_map(|choice| choice["name"].as_str().map(str::to_string))
+                            .collect(),
+                    }))
+                }
+            }
+        }
+        _ => argument,
+    };
+    let out_of_range = || {
+        Err(ParseError::OutOfRange {
+            name: name.to_string(),
+            value: argument.to_string(),
+        })
+    };
+
+    Some(match kind {
+        CommandOptionType::String => {
+            // Discord counts characters, not bytes
+            let length = argument.chars().count() as u64;
+            let min = option["min_length"].as_u64();
+            let max = option["max_length"].as_u64();
+            if min.is_some_and(|min| length < min) || max.is_some_and(|max| length > max) {
+                Err(ParseError::InvalidLength {
+                    name: name.to_string(),
+                    min: min.unwrap_or(0),
+                    max: max.unwrap_or(MAX_LENGTH),
+                })
+            } else {
+                Ok(CommandDataOptionValue::String(argument.to_string()))
+            }
+        }
+        CommandOptionType::Integer => {
+            let int = argument.parse::<i64>().ok()?;
+            if option["min_value"].as_i64().is_some_and(|min| int < min)
+                || option["max_value"].as_i64().is_some_and(|max| int > max)
+            {
+                out_of_range()
+            } else {
+                Ok(CommandDataOptionValue::Integer(int))
+            }
+        }
+        CommandOptionType::Number => {
+            let number = argument.parse::<f64>().ok()?;
+            if option["min_value"].as_f64().is_some_and(|min| number < min)
+                || option["max_value"].as_f64().is_some_and(|max| number > max)
+            {
+                out_of_range()
+            } else {
+                Ok(CommandDataOptionValue::Number(number))
+            }
+        }
+        CommandOptionType::Boolean => Ok(CommandDataOptionValue::Boolean(
+            match argument.to_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => true,
+                "false" | "no" | "off" | "0" => false,
+                _ => return None,
+            },
+        )),
+        CommandOptionType::User => Ok(resolver.user(argument).await?),
+        CommandOptionType::Role => Ok(resolver.role(argument).await?),
+        CommandOptionType::Mentionable => Ok(match resolver.user(argument).await {
+            Some(user) => user,
+            None => resolver.role(argument).await?,
+        }),
+        CommandOptionType::Channel => Ok(resolver.channel(argument).await?),
+        _ => return None,
+    })
+}
+
+/// Text of a choice value, which is a string or a number.
+fn choice_value(value: &Value) -> String {
+    match value.as_str() {
+        Some(value) => value.to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Builds the same option Discord would have sent for an interaction.
+pub(crate) fn to_option(
+    name: String,
+    kind: CommandOptionType,
+    resolved: CommandDataOptionValue,
+) -> CommandDataOption {
+    let value = match &resolved {
+        CommandDataOptionValue::String(string) => Value::from(string.as_str()),
+        CommandDataOptionValue::Integer(int) => Value::from(*int),
+        CommandDataOptionValue::Boolean(boolean) => Value::from(*boolean),
+        CommandDataOptionValue::Number(number) => Value::from(*number),
+        CommandDataOptionValue::User(user, _) => Value::from(user.id.to_string()),
+        CommandDataOptionValue::Role(role) => Value::from(role.id.to_string()),
+        CommandDataOptionValue::Channel(channel) => Value::from(channel.id.to_string()),
+        CommandDataOptionValue::Attachment(attachment) => Value::from(attachment.id.to_string()),
+        _ => Value::Null,
+    };
+
+    // `CommandDataOption` is non-exhaustive, so it can only be built through deserialization
+    let mut option: CommandDataOption = json::prelude::from_value(json::json!({
+        "name": name,
+        "type": kind.num(),
+        "value": value,
+    }))
+    .expect("option built from valid JSON");
+    option.resolved = Some(resolved);
+    option
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Resolves nothing, the parsed layouts only declare plain values.
+    struct NoEntities;
+    #[async_trait]
+    impl Resolver for NoEntities {
+        async fn user(&self, _argument: &str) -> Option<CommandDataOptionValue> {
+            None
+        }
+        async fn role(&self, _argument: &str) -> Option<CommandDataOptionValue> {
+            None
+        }
+        async fn channel(&self, _argument: &str) -> Option<CommandDataOptionValue> {
+            None
+        }
+        fn attachment(&self, _index: usize) -> Option<CommandDataOptionValue> {
+            None
+        }
+    }
+
+    async fn parse_json(
+        declared: Value,
+        input: &str,
+    ) -> Result<Vec<CommandDataOption>, ParseError> {
+        parse(&NoEntities, declared.as_array().unwrap(), input).await
+    }
+
+    fn arguments(input: &str) -> Vec<&str> {
+        let mut arguments = Arguments { input };
+        std::iter::from_fn(|| arguments.next_argument()).collect()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(arguments("  one\ttwo \n three  "), ["one", "two", "three"]);
+        assert!(arguments("   ").is_empty());
+    }
+
+    #[test]
+    fn keeps_quoted_text_together() {
+        assert_eq!(
+            arguments(r#"say "hello there" "" end"#),
+            ["say", "hello there", "", "end"]
+        );
+        // Unterminated quotes are taken literally
+        assert_eq!(arguments(r#""hello there"#), [r#""hello"#, "there"]);
+    }
+
+    #[test]
+    fn rest_takes_everything_left() {
+        let mut arguments = Arguments {
+            input: r#"first  second "third" "#,
+        };
+        assert_eq!(arguments.next_argument(), Some("first"));
+        assert_eq!(arguments.rest(), Some(r#"second "third""#));
+        assert_eq!(arguments.rest(), None);
+        assert_eq!(arguments.next_argument(), None);
+    }
+
+    #[tokio::test]
+    async fn last_text_option_is_greedy() {
+        let options = parse_json(
+            json::json!([
+                { "name": "count", "type": 4, "required": true },
+                { "name": "reason", "type": 3 },
+            ]),
+            r#"5 spam in "general""#,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(options[0].value, Some(Value::from(5)));
+        assert_eq!(options[1].value, Some(Value::from(r#"spam in "general""#)));
+    }
+
+    #[tokio::test]
+    async fn optional_options_can_be_left_out() {
+        let options = parse_json(
+            json::json!([
+                { "name": "silent", "type": 5 },
+                { "name": "reason", "type": 3 },
+            ]),
+            "yes",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].value, Some(Value::from(true)));
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_invalid_and_trailing_arguments() {
+        let declared = json::json!([{
+            "name": "number",
+            "type": 4,
+            "required": true,
+            "min_value": 0,
+            "max_value": 100,
+        }]);
+
+        assert!(matches!(
+            parse_json(declared.clone(), "").await,
+            Err(ParseError::MissingOption(name)) if name == "number"
+        ));
+        assert!(matches!(
+            parse_json(declared.clone(), "ten").await,
+            Err(ParseError::InvalidOption { value, .. }) if value == "ten"
+        ));
+        assert!(matches!(
+            parse_json(declared.clone(), "101").await,
+            Err(ParseError::OutOfRange { value, .. }) if value == "101"
+        ));
+        assert!(matches!(
+            parse_json(declared, "10 20").await,
+            Err(ParseError::TrailingInput(rest)) if rest == "20"
+        ));
+    }
+
+    #[tokio::test]
+    async fn picks_choices_by_name_or_value() {
+        let declared = json::json!([{
+            "name": "period",
+            "type": 4,
+            "required": true,
+            "choices": [
+                { "name": "Day", "value": 1 },
+                { "name": "Week", "value": 7 },
+            ],
+        }]);
+
+        let options = parse_json(declared.clone(), "week").await.unwrap();
+        assert_eq!(options[0].value, Some(Value::from(7)));
+        let options = parse_json(declared.clone(), "1").await.unwrap();
+        assert_eq!(options[0].value, Some(Value::from(1)));
+        assert!(matches!(
+            parse_json(declared, "3").await,
+            Err(ParseError::NotAChoice { value, choices, .. })
+                if value == "3" && choices == ["Day", "Week"]
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_text_of_invalid_length() {
+        let declared = json::json!([{
+            "name": "reason",
+            "type": 3,
+            "required": true,
+            "min_length": 3,
+            "max_length": 5,
+        }]);
+
+        assert!(parse_json(declared.clone(), "späm").await.is_ok());
+        assert!(matches!(
+            parse_json(declared.clone(), "no").await,
+            Err(ParseError::InvalidLength { min: 3, max: 5, .. })
+        ));
+        assert!(matches!(
+            parse_json(declared, "too long").await,
+            Err(ParseError::InvalidLength { min: 3, max: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn renders_errors_from_the_catalog() {
+        let error = ParseError::NotAChoice {
+            name: String::from("period"),
+            value: String::from("3"),
+            choices: vec![String::from("Day"), String::from("Week")],
+        };
+        assert_eq!(
+            error.to_string(),
+            "`3` isn't one of the choices for argument `period`: `Day`, `Week`."
+        );
+        let error = ParseError::InvalidLength {
+            name: String::from("reason"),
+            min: 3,
+            max: 5,
+        };
+        assert_eq!(
+            error.to_string(),
+            "Argument `reason` must be 3 to 5 characters long."
+        );
+    }
+
+    #[tokio::test]
+    async fn nests_options_in_subcommands() {
+        let declared = json::json!([
+            { "name": "case", "type": 2, "options": [
+                { "name": "view", "type": 1, "options": [
+                    { "name": "id", "type": 4, "required": true },
+                ] },
+            ] },
+            { "name": "warn", "type": 1 },
+        ]);
+
+        let options = parse_json(declared.clone(), "case view 7").await.unwrap();
+        let group = &options[0];
+        assert_eq!(group.name, "case");
+        assert_eq!(group.kind, CommandOptionType::SubCommandGroup);
+        let subcommand = &group.options[0];
+        assert_eq!(subcommand.name, "view");
+        assert_eq!(subcommand.kind, CommandOptionType::SubCommand);
+        assert_eq!(subcommand.options[0].name, "id");
+        assert_eq!(subcommand.options[0].value, Some(Value::from(7)));
+
+        let options = parse_json(declared.clone(), "warn").await.unwrap();
+        assert_eq!(options[0].name, "warn");
+        assert!(options[0].options.is_empty());
+
+        assert!(matches!(
+            parse_json(declared.clone(), "").await,
+            Err(ParseError::MissingSubcommand(expected)) if expected == ["case", "warn"]
+        ));
+        assert!(matches!(
+            parse_json(declared.clone(), "case").await,
+            Err(ParseError::MissingSubcommand(expected)) if expected == ["view"]
+        ));
+        assert!(matches!(
+            parse_json(declared, "case edit").await,
+            Err(ParseError::UnknownSubcommand(name)) if name == "edit"
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{sync::Mutex, time::Duration};
+
+use serenity::model::{
+    channel::ReactionType, id::MessageId, prelude::interaction::InteractionResponseType,
+};
+
+use super::{
+    response::{Sent, Sink},
+    Message, Modal, Request, Response,
+};
+
+/// Something delivered to a [Recorder].
+#[derive(Debug, Clone)]
+pub enum Recorded {
+    /// Original response, or a reply to a message command.
+    Response {
+        message: Message,
+        ephemeral: bool,
+    },
+    /// Replacement of the message the interacted component is attached to.
+    Update(Message),
+    /// Deferred original response, or the typing indicator of a message command.
+    Deferred {
+        ephemeral: bool,
+    },
+    /// Deferred replacement of the message the interacted component is attached to.
+    DeferredUpdate,
+    /// Edit of the original response or of another delivered message.
+    Edit {
+        sent: Sent,
+        message: Message,
+    },
+    FollowUp {
+        message: Message,
+        ephemeral: bool,
+    },
+    Modal(Modal),
+    Delete(Sent),
+    Reaction {
+        sent: Sent,
+        reaction: ReactionType,
+    },
+}
+impl Recorded {
+    /// Returns the delivered message, if any.
+    pub fn get_message(&self) -> Option<&Message> {
+        match self {
+            Recorded::Response { message, .. }
+            | Recorded::Update(message)
+            | Recorded::Edit { message, .. }
+            | Recorded::FollowUp { message, .. } => Some(message),
+            _ => None,
+        }
+    }
+}
+
+/// Keeps responses in memory instead of sending them to Discord, so commands can be run in tests.\
+/// Pass it to [Response::from_sink](super::Response::from_sink).
+#[derive(Default)]
+pub struct Recorder {
+    recorded: Mutex<Vec<Recorded>>,
+}
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns everything delivered so far, in order.
+    pub fn recorded(&self) -> Vec<Recorded> {
+        self.recorded.lock().unwrap().clone()
+    }
+
+    /// Returns the content of every delivered message, in order.
+    pub fn contents(&self) -> Vec<String> {
+        self.recorded
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(Recorded::get_message)
+            .map(Message::get_content)
+            .collect()
+    }
+
+    /// Records `recorded`, returning how many things have been delivered so far.
+    fn record(&self, recorded: Recorded) -> u64 {
+        let mut records = self.recorded.lock().unwrap();
+        records.push(recorded);
+        records.len() as u64
+    }
+}
+
+#[async_trait]
+impl Sink for Recorder {
+    async fn respond(
+        &self,
+        kind: InteractionResponseType,
+        message: &Message,
+        ephemeral: bool,
+    ) -> serenity::Result<Sent> {
+        let message = message.clone();
+        self.record(match kind {
+            InteractionResponseType::UpdateMessage => Recorded::Update(message),
+            _ => Recorded::Response { message, ephemeral },
+        });
+        Ok(Sent::Original)
+    }
+
+    async fn defer(&self, kind: InteractionResponseType, ephemeral: bool) -> serenity::Result<()> {
+        self.record(match kind {
+            InteractionResponseType::DeferredUpdateMessage => Recorded::DeferredUpdate,
+            _ => Recorded::Deferred { ephemeral },
+        });
+        Ok(())
+    }
+
+    async fn edit(&self, message: &Message) -> serenity::Result<Sent> {
+        self.record(Recorded::Edit {
+            sent: Sent::Original,
+            message: message.clone(),
+        });
+        Ok(Sent::Original)
+    }
+
+    /// Follow-ups are given the position of their record as an ID.
+    async fn follow_up(&self, message: &Message, ephemeral: bool) -> serenity::Result<Sent> {
+        let id = self.record(Recorded::FollowUp {
+            message: message.clone(),
+            ephemeral,
+        });
+        Ok(Sent::Message(MessageId(id)))
+    }
+
+    async fn open_modal(&self, modal: &Modal) -> serenity::Result<()> {
+        self.record(Recorded::Modal(modal.clone()));
+        Ok(())
+    }
+
+    async fn edit_sent(&self, sent: Sent, message: &Message) -> serenity::Result<()> {
+        self.record(Recorded::Edit {
+            sent,
+            message: message.clone(),
+        });
+        Ok(())
+    }
+
+    async fn delete_sent(&self, sent: Sent) -> serenity::Result<()> {
+        self.record(Recorded::Delete(sent));
+        Ok(())
+    }
+
+    async fn react(&self, sent: Sent, reaction: ReactionType) -> serenity::Result<()> {
+        self.record(Recorded::Reaction { sent, reaction });
+        Ok(())
+    }
+
+    /// Nobody can interact with recorded messages, so this always times out right away.
+    async fn await_component(
+        &self,
+        _sent: Sent,
+        _timeout: Duration,
+    ) -> serenity::Result<Option<(Request, Response)>> {
+        Ok(None)
+    }
+}
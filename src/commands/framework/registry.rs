@@ -0,0 +1,209 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use super::{Command, ComponentHandler};
+
+struct Entry {
+    command: Arc<dyn Command>,
+    category: Option<String>,
+    enabled: bool,
+}
+
+#[derive(Default)]
+struct Commands {
+    entries: HashMap<String, Entry>,
+    /// Aliases of message commands mapped to the name of the command.
+    aliases: HashMap<String, String>,
+    components: HashMap<String, Arc<dyn ComponentHandler>>,
+}
+impl Commands {
+    fn remove(&mut self, name: &str) -> bool {
+        self.aliases.retain(|_, command| command != name);
+        self.entries.remove(name).is_some()
+    }
+}
+
+/// Commands and component handlers known to the bot, can be changed while it's running.\
+/// Commands added after the bot has started aren't available as slash commands until they're registered.
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: RwLock<Commands>,
+}
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a command without a category, replacing any command with the same name.
+    pub fn add(&self, command: impl Command) {
+        self.insert(None, Arc::new(command));
+    }
+    /// Adds a command to a category, e.g. `Moderation`, replacing any command with the same name.
+    pub fn add_in(&self, category: &str, command: impl Command) {
+        self.insert(Some(category.to_string()), Arc::new(command));
+    }
+
+    fn insert(&self, category: Option<String>, command: Arc<dyn Command>) {
+        let name = command.name().to_string();
+        let mut commands = self.commands.write().unwrap();
+        commands.remove(&name);
+        for alias in command.aliases() {
+            commands.aliases.insert(alias.to_string(), name.clone());
+        }
+        commands.entries.insert(
+            name,
+            Entry {
+                command,
+                category,
+                enabled: true,
+            },
+        );
+    }
+
+    /// Removes a command along with its aliases, returns `false` if there's no such command.
+    pub fn remove(&self, name: &str) -> bool {
+        self.commands.write().unwrap().remove(name)
+    }
+
+    /// Lets the message command `name` be invoked as `alias` as well.
+    pub fn alias(&self, name: &str, alias: &str) {
+        self.commands
+            .write()
+            .unwrap()
+            .aliases
+            .insert(alias.to_string(), name.to_string());
+    }
+
+    /// Enables a disabled command, returns `false` if there's no such command.
+    pub fn enable(&self, name: &str) -> bool {
+        self.set_enabled(name, true)
+    }
+    /// Disables a command without unregistering it, users are told it's disabled when they invoke it.
+    /// Returns `false` if there's no such command.
+    pub fn disable(&self, name: &str) -> bool {
+        self.set_enabled(name, false)
+    }
+    fn set_enabled(&self, name: &str, enabled: bool) -> bool {
+        match self.commands.write().unwrap().entries.get_mut(name) {
+            Some(entry) => {
+                entry.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.commands
+            .read()
+            .unwrap()
+            .entries
+            .get(name)
+            .is_some_and(|entry| entry.enabled)
+    }
+
+    /// Looks a command up by its name or one of its aliases, disabled commands included.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Command>> {
+        let commands = self.commands.read().unwrap();
+        let name = commands
+            .aliases
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or(name);
+        commands
+            .entries
+            .get(name)
+            .map(|entry| entry.command.clone())
+    }
+
+    /// Every command sorted by name, disabled commands included.
+    pub fn commands(&self) -> Vec<Arc<dyn Command>> {
+        let commands = self.commands.read().unwrap();
+        let mut commands: Vec<Arc<dyn Command>> = commands
+            .entries
+            .values()
+            .map(|entry| entry.command.clone())
+            .collect();
+        commands.sort_by(|a, b| a.name().cmp(b.name()));
+        commands
+    }
+
+    /// Category of the command, if it was added to one.
+    pub fn category(&self, name: &str) -> Option<String> {
+        self.commands
+            .read()
+            .unwrap()
+            .entries
+            .get(name)
+            .and_then(|entry| entry.category.clone())
+    }
+    /// Names of every category, sorted.
+    pub fn categories(&self) -> Vec<String> {
+        let mut categories: Vec<String> = self
+            .commands
+            .read()
+            .unwrap()
+            .entries
+            .values()
+            .filter_map(|entry| entry.category.clone())
+            .collect();
+        categories.sort();
+        categories.dedup();
+        categories
+    }
+    /// Commands in a category sorted by name.
+    pub fn in_category(&self, category: &str) -> Vec<Arc<dyn Command>> {
+        let commands = self.commands.read().unwrap();
+        let mut commands: Vec<Arc<dyn Command>> = commands
+            .entries
+            .values()
+            .filter(|entry| entry.category.as_deref() == Some(category))
+            .map(|entry| entry.command.clone())
+            .collect();
+        commands.sort_by(|a, b| a.name().cmp(b.name()));
+        commands
+    }
+
+    /// Adds a handler of components which don't belong to any command,
+    /// replacing any handler with the same namespace.
+    pub fn add_component_handler(&self, handler: impl ComponentHandler) {
+        self.commands
+            .write()
+            .unwrap()
+            .components
+            .insert(handler.namespace().to_string(), Arc::new(handler));
+    }
+    pub fn remove_component_handler(&self, namespace: &str) -> bool {
+        self.commands
+            .write()
+            .unwrap()
+            .components
+            .remove(namespace)
+            .is_some()
+    }
+    pub fn get_component_handler(&self, namespace: &str) -> Option<Arc<dyn ComponentHandler>> {
+        self.commands
+            .read()
+            .unwrap()
+            .components
+            .get(namespace)
+            .cloned()
+    }
+}
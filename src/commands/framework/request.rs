@@ -1,5 +1,3 @@
-use std::sync::Arc;
-
 // Copyright (C) 2023 Egor Poleshko
 //
 // This file is part of Eternacore.
@@ -16,56 +14,670 @@ use std::sync::Arc;
 //
 // You should have received a copy of the GNU General Public License
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::sync::Arc;
+
 use serenity::{
+    builder::CreateApplicationCommand,
     http::Http,
-    model::prelude::{
-        command::CommandType,
-        interaction::application_command::{
-            CommandData, CommandDataOption, CommandDataOptionValue,
+    json::Value,
+    model::{
+        channel::Message as SerenityMessage,
+        prelude::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::{
+                    ApplicationCommandInteraction, CommandDataOption, CommandDataOptionValue,
+                    ResolvedTarget,
+                },
+                autocomplete::AutocompleteInteraction,
+                message_component::MessageComponentInteraction,
+                modal::ModalSubmitInteraction,
+            },
+            Channel, ChannelId, GuildId, InteractionId, Member, PartialMember, Permissions, RoleId,
+            Timestamp, User, UserId,
         },
-        ChannelId, GuildId,
     },
+    prelude::Context,
+};
+use tokio::sync::OnceCell;
+
+use super::{
+    arguments::{ArgumentError, FromOption},
+    component,
+    modal::Submission,
+    parser::{self, ParseError},
+    Error, State,
 };
 
 #[derive(Debug, Clone)]
 pub struct Command {
     name: String,
+    path: Vec<String>,
     options: Vec<CommandDataOption>,
 }
 impl Command {
+    fn new(name: String, options: Vec<CommandDataOption>) -> Self {
+        Self {
+            path: vec![name.clone()],
+            name,
+            options,
+        }
+    }
+
     pub fn name(&self) -> String {
         self.name.clone()
     }
 
+    /// Names of the command and every subcommand group and subcommand leading to it,
+    /// e.g. `["mod", "case", "view"]` for `/mod case view`.
+    pub fn path(&self) -> Vec<String> {
+        self.path.clone()
+    }
+
+    /// Returns a subcommand or a subcommand group named `name` without any options.
+    pub(crate) fn descend(&self, name: &str) -> Command {
+        let mut path = self.path.clone();
+        path.push(name.to_string());
+
+        Command {
+            name: name.to_string(),
+            path,
+            options: Vec::new(),
+        }
+    }
+
+    /// Returns the invoked subcommand or subcommand group with options narrowed down to it.
+    pub fn subcommand(&self) -> Option<Command> {
+        let option = self.options.iter().find(|option| {
+            matches!(
+                option.kind,
+                CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup
+            )
+        })?;
+        let mut path = self.path.clone();
+        path.push(option.name.clone());
+
+        Some(Command {
+            name: option.name.clone(),
+            path,
+            options: option.options.clone(),
+        })
+    }
+
     pub fn options(&self) -> Vec<CommandDataOption> {
         self.options.clone()
     }
+
+    /// Returns the name and partial value of the option focused during autocompletion,
+    /// searching through subcommands as well.
+    pub fn focused(&self) -> Option<(String, String)> {
+        fn find(options: &[CommandDataOption]) -> Option<&CommandDataOption> {
+            options.iter().find_map(|option| {
+                if option.focused {
+                    Some(option)
+                } else {
+                    find(&option.options)
+                }
+            })
+        }
+
+        let option = find(&self.options)?;
+        let value = match &option.value {
+            Some(Value::String(string)) => string.clone(),
+            Some(value) => value.to_string(),
+            None => String::new(),
+        };
+        Some((option.name.clone(), value))
+    }
+
+    /// Extracts a required option, failing if it's missing or holds a value of another type.
+    pub fn get<T: FromOption>(&self, name: &str) -> Result<T, ArgumentError> {
+        self.get_optional(name)?
+            .ok_or_else(|| ArgumentError::Missing(name.to_string()))
+    }
+
+    /// Extracts an optional option, failing only if it holds a value of another type.
+    pub fn get_optional<T: FromOption>(&self, name: &str) -> Result<Option<T>, ArgumentError> {
+        let option = match self.options.iter().find(|option| option.name == name) {
+            Some(option) => option,
+            None => return Ok(None),
+        };
+        option
+            .resolved
+            .as_ref()
+            .and_then(T::from_option)
+            .map(Some)
+            .ok_or_else(|| ArgumentError::Mistyped {
+                name: name.to_string(),
+                expected: T::EXPECTED,
+            })
+    }
+}
+
+/// Message component the user interacted with.
+#[derive(Debug, Clone)]
+pub struct Component {
+    id: String,
+    path: Vec<String>,
+    values: Vec<String>,
 }
+impl Component {
+    /// Custom ID of the component without its namespace.
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    /// Path of the command owning the component, taken from the namespace of its custom ID.
+    pub fn path(&self) -> Vec<String> {
+        self.path.clone()
+    }
 
+    /// Values picked in a select menu.
+    pub fn values(&self) -> Vec<String> {
+        self.values.clone()
+    }
+}
+
+/// Guild member who invoked the command, built the same way from interactions and messages.
+#[derive(Debug, Clone)]
+pub struct MemberView {
+    pub guild: GuildId,
+    pub nick: Option<String>,
+    pub roles: Vec<RoleId>,
+    pub joined_at: Option<Timestamp>,
+    pub premium_since: Option<Timestamp>,
+    /// Whether the member hasn't passed membership screening yet.
+    pub pending: bool,
+    /// Permissions of the member in the channel, only sent by Discord along with interactions.\
+    /// [Request::member_permissions] resolves them for message commands as well.
+    pub permissions: Option<Permissions>,
+}
+impl MemberView {
+    fn from_member(member: &Member) -> Self {
+        Self {
+            guild: member.guild_id,
+            nick: member.nick.clone(),
+            roles: member.roles.clone(),
+            joined_at: member.joined_at,
+            premium_since: member.premium_since,
+            pending: member.pending,
+            permissions: member.permissions,
+        }
+    }
+
+    fn from_partial_member(guild: GuildId, member: &PartialMember) -> Self {
+        Self {
+            guild,
+            nick: member.nick.clone(),
+            roles: member.roles.clone(),
+            joined_at: member.joined_at,
+            premium_since: member.premium_since,
+            pending: member.pending,
+            permissions: member.permissions,
+        }
+    }
+}
+
+/// Builds a request out of `$interaction` of any kind, with the fields which differ
+/// between kinds of interactions given after it.
+macro_rules! from_interaction {
+    ($http:expr, $interaction:expr, { $($field:ident: $value:expr),* $(,)? }) => {{
+        let interaction = $interaction;
+        Request {
+            $($field: $value,)*
+            state: Arc::new(State::new()),
+            http: $http,
+            guild: interaction.guild_id,
+            channel: interaction.channel_id,
+            user: interaction.user.clone(),
+            member: interaction.member.as_ref().map(MemberView::from_member),
+            member_permissions: resolved(
+                interaction.guild_id,
+                interaction
+                    .member
+                    .as_ref()
+                    .and_then(|member| member.permissions),
+            ),
+            app_permissions: resolved(interaction.guild_id, interaction.app_permissions),
+            locale: Some(interaction.locale.clone()),
+            guild_locale: interaction.guild_locale.clone(),
+            prefix: None,
+            interaction_id: Some(interaction.id),
+            token: Some(interaction.token.clone()),
+            created_at: interaction.id.created_at(),
+        }
+    }};
+}
+
+#[derive(Clone)]
 pub struct Request {
     command: Command,
+    component: Option<Component>,
+    submission: Option<Submission>,
+    target: Option<ResolvedTarget>,
+    state: Arc<State>,
     pub http: Arc<Http>,
     pub guild: Option<GuildId>,
     pub channel: ChannelId,
+    /// User who invoked the command or interacted with the component.
+    pub user: User,
+    /// Member view of the user, only in guilds.
+    pub member: Option<MemberView>,
+    /// Permissions of the user in the channel, resolved by [Request::member_permissions].
+    member_permissions: Arc<OnceCell<Option<Permissions>>>,
+    /// Permissions of the bot in the channel, resolved by [Request::app_permissions].
+    app_permissions: Arc<OnceCell<Option<Permissions>>>,
+    /// Locale of the user's client, only sent by Discord along with interactions.
+    pub locale: Option<String>,
+    /// Preferred locale of the guild, only sent by Discord along with interactions in guilds.
+    pub guild_locale: Option<String>,
+    /// Prefix the message command was invoked with, interactions don't have one.
+    pub prefix: Option<String>,
+    /// ID of the interaction, message commands don't have one.
+    pub interaction_id: Option<InteractionId>,
+    /// Token of the interaction, which can be used to respond to it for 15 minutes.
+    pub token: Option<String>,
+    /// When the command was invoked or the component was interacted with.
+    pub created_at: Timestamp,
 }
 impl Request {
-    pub fn from_command_data_option_slice(
+    /// Starts building a request for the command `name` by hand, e.g. to run the command in a test.
+    pub fn builder(name: impl ToString) -> RequestBuilder {
+        RequestBuilder::new(name)
+    }
+
+    pub fn from_interaction_command(
         http: Arc<Http>,
-        channel: ChannelId,
-        command_data: &CommandData,
+        interaction: &ApplicationCommandInteraction,
+    ) -> Self {
+        from_interaction!(http, interaction, {
+            command: Command::new(
+                interaction.data.name.clone(),
+                interaction.data.options.clone(),
+            ),
+            component: None,
+            submission: None,
+            target: interaction.data.target(),
+        })
+    }
+
+    pub fn from_autocomplete(http: Arc<Http>, interaction: &AutocompleteInteraction) -> Self {
+        from_interaction!(http, interaction, {
+            command: Command::new(
+                interaction.data.name.clone(),
+                interaction.data.options.clone(),
+            ),
+            component: None,
+            submission: None,
+            target: None,
+        })
+    }
+
+    /// Builds a request for the message command `name` invoked with `prefix`,
+    /// its options are parsed by [Request::parse_arguments].
+    pub fn from_message(
+        context: &Context,
+        message: &SerenityMessage,
+        prefix: &str,
+        name: &str,
     ) -> Self {
         Self {
-            command: Command {
-                name: command_data.name.clone(),
-                options: command_data.options.clone(),
-            },
-            http,
-            guild: command_data.guild_id,
-            channel,
+            command: Command::new(name.to_string(), Vec::new()),
+            component: None,
+            submission: None,
+            target: None,
+            state: Arc::new(State::new()),
+            http: context.http.clone(),
+            guild: message.guild_id,
+            channel: message.channel_id,
+            user: message.author.clone(),
+            member: message.guild_id.and_then(|guild| {
+                message
+                    .member
+                    .as_ref()
+                    .map(|member| MemberView::from_partial_member(guild, member))
+            }),
+            member_permissions: Arc::new(OnceCell::new()),
+            app_permissions: Arc::new(OnceCell::new()),
+            locale: None,
+            guild_locale: None,
+            prefix: Some(prefix.to_string()),
+            interaction_id: None,
+            token: None,
+            created_at: message.timestamp,
         }
     }
 
+    /// Parses the text following the command name in a message
+    /// according to the options declared in `layout`.
+    pub async fn parse_arguments(
+        mut self,
+        context: &Context,
+        message: &SerenityMessage,
+        layout: &CreateApplicationCommand,
+        input: &str,
+    ) -> Result<Self, ParseError> {
+        let options = parser::parse_options(context, message, layout, input).await?;
+        self.command = Command::new(self.command.name(), options);
+        Ok(self)
+    }
+
+    /// Builds a request from an interaction with a message component,
+    /// the command is the first segment of the custom ID namespace and has no options.
+    pub fn from_component(http: Arc<Http>, interaction: &MessageComponentInteraction) -> Self {
+        let (namespace, id) = component::split_custom_id(&interaction.data.custom_id);
+        let path = component::decode_path(namespace);
+
+        from_interaction!(http, interaction, {
+            command: Command::new(path[0].clone(), Vec::new()),
+            component: Some(Component {
+                id: id.to_string(),
+                path,
+                values: interaction.data.values.clone(),
+            }),
+            submission: None,
+            target: None,
+        })
+    }
+
+    /// Builds a request from a submitted modal,
+    /// the command is the first segment of the custom ID namespace and has no options.
+    pub fn from_modal_submit(http: Arc<Http>, interaction: &ModalSubmitInteraction) -> Self {
+        let (namespace, id) = component::split_custom_id(&interaction.data.custom_id);
+        let path = component::decode_path(namespace);
+
+        from_interaction!(http, interaction, {
+            command: Command::new(path[0].clone(), Vec::new()),
+            component: None,
+            submission: Some(Submission::new(
+                id.to_string(),
+                path,
+                &interaction.data.components,
+            )),
+            target: None,
+        })
+    }
+
     pub fn command(&self) -> Command {
         self.command.clone()
     }
+
+    /// Returns the interacted component if the request comes from one.
+    pub fn component(&self) -> Option<Component> {
+        self.component.clone()
+    }
+
+    /// Returns the values of the submitted modal if the request comes from one.
+    pub fn submission(&self) -> Option<Submission> {
+        self.submission.clone()
+    }
+
+    /// Returns the user a user context menu command was invoked on.
+    pub fn target_user(&self) -> Option<User> {
+        match &self.target {
+            Some(ResolvedTarget::User(user, _)) => Some(user.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the member a user context menu command was invoked on, if it was invoked in a guild.
+    pub fn target_member(&self) -> Option<PartialMember> {
+        match &self.target {
+            Some(ResolvedTarget::User(_, member)) => member.as_deref().cloned(),
+            _ => None,
+        }
+    }
+
+    /// Returns the message a message context menu command was invoked on.
+    pub fn target_message(&self) -> Option<SerenityMessage> {
+        match &self.target {
+            Some(ResolvedTarget::Message(message)) => Some(message.as_ref().clone()),
+            _ => None,
+        }
+    }
+
+    /// Namespaces `id` with the path of the current command,
+    /// so interactions with the component or modal are routed back to
+    /// [super::Command::component] or [super::Command::modal].
+    pub fn custom_id(&self, id: &str) -> String {
+        component::custom_id(&component::encode_path(&self.command.path), id)
+    }
+
+    /// Roles of the user, empty outside of guilds.
+    pub fn roles(&self) -> Vec<RoleId> {
+        self.member
+            .as_ref()
+            .map(|member| member.roles.clone())
+            .unwrap_or_default()
+    }
+
+    /// Permissions of the user in the channel, calculated once if Discord didn't send them.\
+    /// Returns `None` outside of guilds.
+    pub async fn member_permissions(&self) -> Result<Option<Permissions>, Error> {
+        let permissions = self
+            .member_permissions
+            .get_or_try_init(|| async {
+                match self.guild {
+                    Some(guild) => channel_permissions(self, guild, self.user.id)
+                        .await
+                        .map(Some),
+                    None => Ok(None),
+                }
+            })
+            .await?;
+        Ok(*permissions)
+    }
+
+    /// Permissions of the bot in the channel, calculated once if Discord didn't send them.\
+    /// Returns `None` outside of guilds.
+    pub async fn app_permissions(&self) -> Result<Option<Permissions>, Error> {
+        let permissions = self
+            .app_permissions
+            .get_or_try_init(|| async {
+                let guild = match self.guild {
+                    Some(guild) => guild,
+                    None => return Ok(None),
+                };
+                let bot = self.http.get_current_user().await?.id;
+                channel_permissions(self, guild, bot).await.map(Some)
+            })
+            .await?;
+        Ok(*permissions)
+    }
+
+    /// Returns the value of type `T` from the shared application state.
+    pub fn state<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.state.get::<T>()
+    }
+
+    /// Replaces the shared application state, which is empty by default.
+    pub fn with_state(mut self, state: Arc<State>) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Replaces the command, used to descend into subcommands.
+    pub(crate) fn with_command(mut self, command: Command) -> Self {
+        self.command = command;
+        self
+    }
+}
+
+/// Request built by hand instead of from an interaction or a message, see [Request::builder].\
+/// Outside of guilds nothing is fetched from Discord, so such requests work without a connection.
+pub struct RequestBuilder {
+    name: String,
+    options: Vec<CommandDataOption>,
+    state: Arc<State>,
+    http: Arc<Http>,
+    guild: Option<GuildId>,
+    channel: ChannelId,
+    user: User,
+    member: Option<MemberView>,
+    app_permissions: Option<Permissions>,
+    locale: Option<String>,
+    guild_locale: Option<String>,
+    prefix: Option<String>,
+}
+impl RequestBuilder {
+    fn new(name: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            options: Vec::new(),
+            state: Arc::new(State::new()),
+            http: Arc::new(Http::new("")),
+            guild: None,
+            channel: ChannelId(0),
+            user: User::default(),
+            member: None,
+            app_permissions: None,
+            locale: None,
+            guild_locale: None,
+            prefix: None,
+        }
+    }
+
+    /// Adds an option, its type is taken from the variant of `value`.
+    pub fn option(mut self, name: impl ToString, value: CommandDataOptionValue) -> Self {
+        let kind = match &value {
+            CommandDataOptionValue::String(_) => CommandOptionType::String,
+            CommandDataOptionValue::Integer(_) => CommandOptionType::Integer,
+            CommandDataOptionValue::Boolean(_) => CommandOptionType::Boolean,
+            CommandDataOptionValue::User(_, _) => CommandOptionType::User,
+            CommandDataOptionValue::Channel(_) => CommandOptionType::Channel,
+            CommandDataOptionValue::Role(_) => CommandOptionType::Role,
+            CommandDataOptionValue::Number(_) => CommandOptionType::Number,
+            CommandDataOptionValue::Attachment(_) => CommandOptionType::Attachment,
+            _ => CommandOptionType::Unknown,
+        };
+        self.options
+            .push(parser::to_option(name.to_string(), kind, value));
+        self
+    }
+
+    /// Replaces the shared application state, which is empty by default.
+    pub fn state(mut self, state: Arc<State>) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Replaces the HTTP client, which has no token by default.
+    pub fn http(mut self, http: Arc<Http>) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Sets the guild the command was invoked in.\
+    /// Permissions which aren't given are fetched from Discord in guilds.
+    pub fn guild(mut self, guild: GuildId) -> Self {
+        self.guild = Some(guild);
+        self
+    }
+
+    pub fn channel(mut self, channel: ChannelId) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Replaces the user who invoked the command, a default user by default.
+    pub fn user(mut self, user: User) -> Self {
+        self.user = user;
+        self
+    }
+
+    /// Sets the member view of the user, its permissions are returned by [Request::member_permissions].
+    pub fn member(mut self, member: MemberView) -> Self {
+        self.member = Some(member);
+        self
+    }
+
+    /// Sets the permissions returned by [Request::app_permissions].
+    pub fn app_permissions(mut self, permissions: Permissions) -> Self {
+        self.app_permissions = Some(permissions);
+        self
+    }
+
+    pub fn locale(mut self, locale: impl ToString) -> Self {
+        self.locale = Some(locale.to_string());
+        self
+    }
+
+    pub fn guild_locale(mut self, locale: impl ToString) -> Self {
+        self.guild_locale = Some(locale.to_string());
+        self
+    }
+
+    /// Makes the request look like it comes from a message command invoked with `prefix`.
+    pub fn prefix(mut self, prefix: impl ToString) -> Self {
+        self.prefix = Some(prefix.to_string());
+        self
+    }
+
+    pub fn build(self) -> Request {
+        let member_permissions = self.member.as_ref().and_then(|member| member.permissions);
+        Request {
+            command: Command::new(self.name, self.options),
+            component: None,
+            submission: None,
+            target: None,
+            state: self.state,
+            http: self.http,
+            guild: self.guild,
+            channel: self.channel,
+            user: self.user,
+            member: self.member,
+            member_permissions: resolved(self.guild, member_permissions),
+            app_permissions: resolved(self.guild, self.app_permissions),
+            locale: self.locale,
+            guild_locale: self.guild_locale,
+            prefix: self.prefix,
+            interaction_id: None,
+            token: None,
+            created_at: Timestamp::now(),
+        }
+    }
+}
+
+/// Permissions sent by Discord are already resolved, outside of guilds there are none to resolve.
+fn resolved(
+    guild: Option<GuildId>,
+    permissions: Option<Permissions>,
+) -> Arc<OnceCell<Option<Permissions>>> {
+    match (guild, permissions) {
+        (Some(_), None) => Arc::new(OnceCell::new()),
+        (Some(_), permissions) => Arc::new(OnceCell::new_with(Some(permissions))),
+        (None, _) => Arc::new(OnceCell::new_with(Some(None))),
+    }
+}
+
+/// Calculates permissions of `user` in the channel of the request,
+/// Discord doesn't send them along with messages.
+async fn channel_permissions(
+    req: &Request,
+    guild: GuildId,
+    user: UserId,
+) -> Result<Permissions, Error> {
+    let channel = match req.channel.to_channel(&req.http).await? {
+        Channel::Guild(channel) => channel,
+        _ => return Ok(Permissions::empty()),
+    };
+    let guild = guild.to_partial_guild(&req.http).await?;
+    let member = guild.member(&req.http, user).await?;
+    Ok(guild.user_permissions_in(&channel, &member)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn there_are_no_permissions_outside_of_guilds() {
+        let all = Some(Permissions::all());
+
+        assert_eq!(resolved(None, all).get(), Some(&None));
+        assert_eq!(resolved(None, None).get(), Some(&None));
+        assert_eq!(resolved(Some(GuildId(1)), all).get(), Some(&all));
+        // Left to be calculated on first use
+        assert_eq!(resolved(Some(GuildId(1)), None).get(), None);
+    }
 }
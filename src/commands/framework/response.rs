@@ -1,5 +1,3 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-
 // Copyright (C) 2023 Egor Poleshko
 //
 // This file is part of Eternacore.
@@ -16,129 +14,838 @@ use std::sync::atomic::{AtomicBool, Ordering};
 //
 // You should have received a copy of the GNU General Public License
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{sync::Arc, time::Duration};
+
+use fluent_bundle::FluentArgs;
 use serenity::{
+    collector::CollectComponentInteraction,
     http::HttpError,
     model::{
-        channel::Message as SerenityMessage,
+        channel::{Message as SerenityMessage, ReactionType},
+        id::{ChannelId, MessageId},
         prelude::interaction::{
-            application_command::ApplicationCommandInteraction, InteractionResponseType,
+            application_command::ApplicationCommandInteraction,
+            message_component::MessageComponentInteraction, modal::ModalSubmitInteraction,
+            InteractionResponseType,
         },
     },
     prelude::Context,
 };
+use tokio::{sync::Mutex, task::JoinHandle};
 
-pub enum Response {
-    InteractionCommand {
-        context: Context,
-        command: ApplicationCommandInteraction,
-    },
-    Message {
-        context: Context,
-        message: SerenityMessage,
-    },
+use super::{Catalog, Error, Message, Modal, Request, State};
+
+/// Evaluates `$body` with `$interaction` bound to the interaction of any kind,
+/// or `$otherwise` with `$origin` bound to the message of a message command.
+macro_rules! on_interaction {
+    ($source:expr, $interaction:ident => $body:expr, $origin:ident => $otherwise:expr) => {
+        match $source {
+            Source::InteractionCommand($interaction) => $body,
+            Source::Component($interaction) => $body,
+            Source::Modal($interaction) => $body,
+            Source::Message($origin) => $otherwise,
+        }
+    };
+}
+
+/// Reason editing the original response of an interaction fails, see [Message::has_files_or_flags].
+const NOT_EDITABLE: &str =
+    "files, TTS and suppressed embeds can't be set when editing the original response";
+
+/// Reason opening a modal fails once anything else has been sent.
+const ALREADY_ACKNOWLEDGED: &str =
+    "modals can't be opened after the interaction has been acknowledged";
+
+/// What the response is being made to.
+#[derive(Clone)]
+enum Source {
+    InteractionCommand(ApplicationCommandInteraction),
+    Message(SerenityMessage),
+    Component(Box<MessageComponentInteraction>),
+    Modal(Box<ModalSubmitInteraction>),
+}
+
+/// Kind of what the response is being made to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    InteractionCommand,
+    Message,
+    Component,
+    Modal,
+}
+
+/// Message delivered by a [Sink].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sent {
+    /// Original response of the interaction, or the message of the interacted component.
+    Original,
+    /// Follow-up message, or a reply to a message command.
+    Message(MessageId),
+}
+
+/// Delivers responses, [Response] picks which method to call based on what has already been sent.\
+/// Responses go to Discord, unless a [Recorder](super::Recorder) or another sink is given to [Response::from_sink].
+#[async_trait]
+pub trait Sink: Send + Sync + 'static {
+    /// Sends the original response, or replaces the interacted message if `kind` is
+    /// [InteractionResponseType::UpdateMessage]. Message commands are replied to instead.
+    async fn respond(
+        &self,
+        kind: InteractionResponseType,
+        message: &Message,
+        ephemeral: bool,
+    ) -> serenity::Result<Sent>;
+    /// Acknowledges the interaction with a deferred response of `kind`,
+    /// message commands trigger the typing indicator instead.
+    async fn defer(&self, kind: InteractionResponseType, ephemeral: bool) -> serenity::Result<()>;
+    /// Edits the original response, or the first reply to a message command.
+    async fn edit(&self, message: &Message) -> serenity::Result<Sent>;
+    /// Sends a follow-up message, message commands are replied to again.
+    async fn follow_up(&self, message: &Message, ephemeral: bool) -> serenity::Result<Sent>;
+    /// Opens a modal in response to a command or a component.
+    async fn open_modal(&self, modal: &Modal) -> serenity::Result<()>;
+    /// Edits a message delivered earlier.
+    async fn edit_sent(&self, sent: Sent, message: &Message) -> serenity::Result<()>;
+    /// Deletes a message delivered earlier.
+    async fn delete_sent(&self, sent: Sent) -> serenity::Result<()>;
+    /// Adds a reaction of the bot to a message delivered earlier.
+    async fn react(&self, sent: Sent, reaction: ReactionType) -> serenity::Result<()>;
+    /// Waits up to `timeout` for an interaction with a component of a message delivered earlier.
+    async fn await_component(
+        &self,
+        sent: Sent,
+        timeout: Duration,
+    ) -> serenity::Result<Option<(Request, Response)>>;
+}
+
+/// Sends responses to Discord.
+struct Discord {
+    context: Context,
+    source: Arc<Source>,
+    /// First reply to a message command, stands in for the original response of an interaction.
+    reply: std::sync::Mutex<Option<MessageId>>,
+}
+impl Discord {
+    /// Replies to a message command, messages can't be ephemeral so the reply is always public.
+    async fn reply(&self, origin: &SerenityMessage, message: &Message) -> serenity::Result<Sent> {
+        let reply = origin
+            .channel_id
+            .send_message(&self.context, |reply| {
+                message.build_message(reply).reference_message(origin);
+                if message.get_allowed_mentions().is_none() {
+                    reply.allowed_mentions(|mentions| mentions.replied_user(false));
+                }
+                reply
+            })
+            .await?;
+        self.reply.lock().unwrap().get_or_insert(reply.id);
+        Ok(Sent::Message(reply.id))
+    }
+
+    fn channel(&self) -> ChannelId {
+        on_interaction!(
+            &*self.source,
+            interaction => interaction.channel_id,
+            origin => origin.channel_id
+        )
+    }
+
+    /// Returns the ID of a delivered message, the original response has to be fetched to know it.
+    async fn message_id(&self, sent: Sent) -> serenity::Result<MessageId> {
+        match sent {
+            Sent::Message(id) => Ok(id),
+            Sent::Original => on_interaction!(
+                &*self.source,
+                interaction => interaction
+                    .get_interaction_response(&self.context)
+                    .await
+                    .map(|response| response.id),
+                _origin => (*self.reply.lock().unwrap())
+                    .ok_or(serenity::Error::Other("nothing has been sent yet"))
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for Discord {
+    async fn respond(
+        &self,
+        kind: InteractionResponseType,
+        message: &Message,
+        ephemeral: bool,
+    ) -> serenity::Result<Sent> {
+        on_interaction!(
+            &*self.source,
+            interaction => interaction
+                .create_interaction_response(&self.context, |response| {
+                    response.kind(kind).interaction_response_data(|data| {
+                        message.build_interaction_response(data).ephemeral(ephemeral)
+                    })
+                })
+                .await
+                .map(|_| Sent::Original),
+            origin => self.reply(origin, message).await
+        )
+    }
+
+    async fn defer(&self, kind: InteractionResponseType, ephemeral: bool) -> serenity::Result<()> {
+        on_interaction!(
+            &*self.source,
+            interaction => interaction
+                .create_interaction_response(&self.context, |response| {
+                    response
+                        .kind(kind)
+                        .interaction_response_data(|data| data.ephemeral(ephemeral))
+                })
+                .await,
+            origin => origin.channel_id.broadcast_typing(&self.context).await
+        )
+    }
+
+    async fn edit(&self, message: &Message) -> serenity::Result<Sent> {
+        on_interaction!(
+            &*self.source,
+            interaction => {
+                // Fails instead of silently dropping what editing can't set
+                if message.has_files_or_flags() {
+                    return Err(serenity::Error::Other(NOT_EDITABLE));
+                }
+                interaction
+                    .edit_original_interaction_response(&self.context, |response| {
+                        message.build_edit_response(response)
+                    })
+                    .await
+                    .map(|_| Sent::Original)
+            },
+            origin => {
+                let reply = *self.reply.lock().unwrap();
+                match reply {
+                    Some(reply) => origin
+                        .channel_id
+                        .edit_message(&self.context, reply, |edit| {
+                            message.build_edit_message(edit)
+                        })
+                        .await
+                        .map(|_| Sent::Message(reply)),
+                    None => self.reply(origin, message).await,
+                }
+            }
+        )
+    }
+
+    async fn follow_up(&self, message: &Message, ephemeral: bool) -> serenity::Result<Sent> {
+        on_interaction!(
+            &*self.source,
+            interaction => interaction
+                .create_followup_message(&self.context, |followup| {
+                    message.build_followup(followup).ephemeral(ephemeral)
+                })
+                .await
+                .map(|followup| Sent::Message(followup.id)),
+            origin => self.reply(origin, message).await
+        )
+    }
+
+    async fn open_modal(&self, modal: &Modal) -> serenity::Result<()> {
+        match &*self.source {
+            Source::InteractionCommand(command) => {
+                command
+                    .create_interaction_response(&self.context, |response| {
+                        response
+                            .kind(InteractionResponseType::Modal)
+                            .interaction_response_data(|data| modal.build(data))
+                    })
+                    .await
+            }
+            Source::Component(component) => {
+                component
+                    .create_interaction_response(&self.context, |response| {
+                        response
+                            .kind(InteractionResponseType::Modal)
+                            .interaction_response_data(|data| modal.build(data))
+                    })
+                    .await
+            }
+            _ => Err(serenity::Error::Other(
+                "modals can only be opened in response to commands and components",
+            )),
+        }
+    }
+
+    async fn edit_sent(&self, sent: Sent, message: &Message) -> serenity::Result<()> {
+        let result = match (&*self.source, sent) {
+            (Source::Message(_), sent) => {
+                let id = self.message_id(sent).await?;
+                self.channel()
+                    .edit_message(&self.context, id, |edit| message.build_edit_message(edit))
+                    .await
+            }
+            (source, Sent::Original) => on_interaction!(
+                source,
+                interaction => {
+                    if message.has_files_or_flags() {
+                        return Err(serenity::Error::Other(NOT_EDITABLE));
+                    }
+                    interaction
+                        .edit_original_interaction_response(&self.context, |response| {
+                            message.build_edit_response(response)
+                        })
+                        .await
+                },
+                _origin => unreachable!()
+            ),
+            // Follow-ups are edited through the interaction, as ephemeral ones can't be edited otherwise
+            (source, Sent::Message(id)) => on_interaction!(
+                source,
+                interaction => interaction
+                    .edit_followup_message(&self.context, id, |followup| {
+                        message.build_followup(followup)
+                    })
+                    .await,
+                _origin => unreachable!()
+            ),
+        };
+        result.map(|_| ())
+    }
+
+    async fn delete_sent(&self, sent: Sent) -> serenity::Result<()> {
+        match (&*self.source, sent) {
+            (Source::Message(_), sent) => {
+                let id = self.message_id(sent).await?;
+                self.channel().delete_message(&self.context, id).await
+            }
+            (source, Sent::Original) => on_interaction!(
+                source,
+                interaction => interaction.delete_original_interaction_response(&self.context).await,
+                _origin => unreachable!()
+            ),
+            (source, Sent::Message(id)) => on_interaction!(
+                source,
+                interaction => interaction.delete_followup_message(&self.context, id).await,
+                _origin => unreachable!()
+            ),
+        }
+    }
+
+    async fn react(&self, sent: Sent, reaction: ReactionType) -> serenity::Result<()> {
+        let id = self.message_id(sent).await?;
+        self.channel()
+            .create_reaction(&self.context, id, reaction)
+            .await
+    }
+
+    async fn await_component(
+        &self,
+        sent: Sent,
+        timeout: Duration,
+    ) -> serenity::Result<Option<(Request, Response)>> {
+        let id = self.message_id(sent).await?;
+        let interaction = CollectComponentInteraction::new(&self.context.shard)
+            .message_id(id)
+            .timeout(timeout)
+            .await;
+        Ok(interaction.map(|interaction| {
+            (
+                Request::from_component(self.context.http.clone(), &interaction),
+                Response::from_component(self.context.clone(), (*interaction).clone()),
+            )
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Acknowledgement {
+    /// Nothing has been sent yet.
+    Pending,
+    /// Discord shows a loading state until the original response is filled in,
+    /// the placeholder is ephemeral if the response was deferred as such.
+    Deferred { ephemeral: bool },
+    /// The interaction was acknowledged without a loading state,
+    /// the interacted message can still be replaced.
+    DeferredUpdate,
+    /// The original response has been sent, further messages are follow-ups.
+    Responded,
+}
+
+/// Response to a command, component or modal.\
+/// Clones share the acknowledgement state, so a response can be made from a spawned task as well.
+#[derive(Clone)]
+pub struct Response {
+    sink: Arc<dyn Sink>,
+    origin: Origin,
+    locale: Option<String>,
+    /// Context and source of responses sent to Discord, used by the `to_*` conversions.
+    discord: Option<(Context, Arc<Source>)>,
+    acknowledgement: Arc<Mutex<Acknowledgement>>,
+    catalog: Arc<Catalog>,
+    state: Arc<State>,
 }
 impl Response {
+    fn new(context: Context, source: Source) -> Self {
+        let origin = match &source {
+            Source::InteractionCommand(_) => Origin::InteractionCommand,
+            Source::Message(_) => Origin::Message,
+            Source::Component(_) => Origin::Component,
+            Source::Modal(_) => Origin::Modal,
+        };
+        let locale = on_interaction!(
+            &source,
+            interaction => Some(interaction.locale.clone()),
+            _message => None
+        );
+        let source = Arc::new(source);
+        let sink = Discord {
+            context: context.clone(),
+            source: source.clone(),
+            reply: std::sync::Mutex::new(None),
+        };
+
+        let mut response = Self::from_sink(Arc::new(sink), origin);
+        response.locale = locale;
+        response.discord = Some((context, source));
+        response
+    }
+
     pub fn from_interaction_command(
         context: Context,
         interaction_command: ApplicationCommandInteraction,
     ) -> Self {
-        Self::InteractionCommand {
-            context,
-            command: interaction_command,
-        }
+        Self::new(context, Source::InteractionCommand(interaction_command))
     }
     pub fn from_message(context: Context, message: SerenityMessage) -> Self {
-        Self::Message { context, message }
+        Self::new(context, Source::Message(message))
+    }
+    pub fn from_component(context: Context, component: MessageComponentInteraction) -> Self {
+        Self::new(context, Source::Component(Box::new(component)))
+    }
+
+    pub fn from_modal_submit(context: Context, modal: ModalSubmitInteraction) -> Self {
+        Self::new(context, Source::Modal(Box::new(modal)))
+    }
+
+    /// Creates a response delivered to `sink` instead of Discord, made to something of the `origin` kind.
+    pub fn from_sink(sink: Arc<dyn Sink>, origin: Origin) -> Self {
+        Self {
+            sink,
+            origin,
+            locale: None,
+            discord: None,
+            acknowledgement: Arc::new(Mutex::new(Acknowledgement::Pending)),
+            catalog: Arc::new(Catalog::new()),
+            state: Arc::new(State::new()),
+        }
+    }
+
+    /// Replaces the catalog messages are rendered from, which holds only the built-in messages by default.
+    pub fn with_catalog(mut self, catalog: Arc<Catalog>) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Replaces the shared application state passed on to requests of awaited components,
+    /// which is empty by default.
+    pub fn with_state(mut self, state: Arc<State>) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Replaces the locale messages are rendered in, which is taken from the interaction by default.
+    pub fn with_locale(mut self, locale: impl ToString) -> Self {
+        self.locale = Some(locale.to_string());
+        self
+    }
+
+    /// Locale of the user's client, message commands don't have one.
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    /// Renders the message `key` from the catalog in the locale of the user.
+    pub fn render(&self, key: &str, args: Option<&FluentArgs>) -> String {
+        self.catalog.render(self.locale(), key, args)
     }
 
-    pub async fn send_message(&self, message: impl Into<super::Message>) {
+    pub async fn send_message(&self, message: impl Into<Message>) -> Result<SentMessage, Error> {
         self.send_ephemeral_message(message, false).await
     }
 
+    /// Sends the original response, or fills it in if the response was deferred.\
+    /// Every following message is sent as a follow-up.
     pub async fn send_ephemeral_message(
         &self,
-        message: impl Into<super::Message>,
+        message: impl Into<Message>,
         ephemeral: bool,
-    ) {
+    ) -> Result<SentMessage, Error> {
         let message = message.into();
-        match self {
-            Response::InteractionCommand { context, command } => {
-                if let Err(why) = command
-                    .create_interaction_response(context, |response| {
-                        response
-                            .kind(InteractionResponseType::ChannelMessageWithSource)
-                            .interaction_response_data(|data| {
-                                data.content(message.get_content()).ephemeral(ephemeral)
-                            })
-                    })
+        let mut acknowledgement = self.acknowledgement.lock().await;
+        match *acknowledgement {
+            Acknowledgement::Pending => {
+                self.respond(
+                    &mut acknowledgement,
+                    InteractionResponseType::ChannelMessageWithSource,
+                    &message,
+                    ephemeral,
+                )
+                .await
+            }
+            Acknowledgement::Deferred {
+                ephemeral: deferred,
+            } => {
+                self.fill(&mut acknowledgement, &message, deferred, ephemeral)
                     .await
-                {
-                    if let serenity::Error::Http(http_error) = why {
-                        if let HttpError::UnsuccessfulRequest(http_error_response) = *http_error {
-                            if http_error_response.error.code == 40060 {
-                                // interaction has already been acknowledged
-                                if let Err(why) = command
-                                    .create_followup_message(context, |followup| {
-                                        followup.content(message.get_content())
-                                    })
-                                    .await
-                                {
-                                    error!(
-                                        "Couldn't make a follow-up message to interaction command `{}`: {}",
-                                        command.id,
-                                        why.to_string()
-                                    );
-                                }
-                            } else {
-                                error!(
-                                    "Couldn't respond to an interaction command `{}`: {}",
-                                    command.id, http_error_response.error.message
-                                );
-                            }
-                        }
-                    }
+            }
+            Acknowledgement::DeferredUpdate | Acknowledgement::Responded => {
+                self.follow_up(&message, ephemeral).await
+            }
+        }
+    }
+
+    /// Replaces the message the interacted component (or the component which opened the modal) is attached to.\
+    /// Commands don't have such message, so a new one is sent instead.
+    pub async fn update_message(&self, message: impl Into<Message>) -> Result<SentMessage, Error> {
+        if !self.is_component() && !self.is_modal() {
+            return self.send_message(message).await;
+        }
+        let message = message.into();
+        let mut acknowledgement = self.acknowledgement.lock().await;
+        match *acknowledgement {
+            Acknowledgement::Pending => {
+                self.respond(
+                    &mut acknowledgement,
+                    InteractionResponseType::UpdateMessage,
+                    &message,
+                    false,
+                )
+                .await
+            }
+            Acknowledgement::Deferred { ephemeral } => {
+                self.fill(&mut acknowledgement, &message, ephemeral, ephemeral)
+                    .await
+            }
+            _ => self.edit(&mut acknowledgement, &message).await,
+        }
+    }
+
+    /// Acknowledges the interaction, giving the command 15 minutes instead of 3 seconds to respond.\
+    /// Discord shows that the bot is thinking until a message is sent or the original response is edited.
+    /// Message commands trigger the typing indicator instead.
+    pub async fn defer(&self, ephemeral: bool) -> Result<(), Error> {
+        let mut acknowledgement = self.acknowledgement.lock().await;
+        if *acknowledgement != Acknowledgement::Pending {
+            return Ok(());
+        }
+        match self
+            .sink
+            .defer(
+                InteractionResponseType::DeferredChannelMessageWithSource,
+                ephemeral,
+            )
+            .await
+        {
+            // Typing doesn't acknowledge anything, the first reply is still the original response
+            Ok(()) if self.is_message_command() => {}
+            Ok(()) => *acknowledgement = Acknowledgement::Deferred { ephemeral },
+            Err(why) if is_already_acknowledged(&why) => {
+                *acknowledgement = Acknowledgement::Responded
+            }
+            Err(why) => return Err(why.into()),
+        }
+        Ok(())
+    }
+
+    /// Acknowledges an interaction with a component or a modal without showing a loading state,
+    /// the message it's attached to can be replaced later with [Response::update_message].\
+    /// Commands are deferred with [Response::defer] instead.
+    pub async fn defer_update(&self) -> Result<(), Error> {
+        if !self.is_component() && !self.is_modal() {
+            return self.defer(false).await;
+        }
+        let mut acknowledgement = self.acknowledgement.lock().await;
+        if *acknowledgement != Acknowledgement::Pending {
+            return Ok(());
+        }
+        match self
+            .sink
+            .defer(InteractionResponseType::DeferredUpdateMessage, false)
+            .await
+        {
+            Ok(()) => *acknowledgement = Acknowledgement::DeferredUpdate,
+            Err(why) if is_already_acknowledged(&why) => {
+                *acknowledgement = Acknowledgement::Responded
+            }
+            Err(why) => return Err(why.into()),
+        }
+        Ok(())
+    }
+
+    /// Defers the response in the background unless something is sent within `delay`.\
+    /// Aborting the returned handle cancels it, e.g. once the command has finished.
+    /// Failing to defer is only logged, as nothing is waiting for the result.
+    pub fn defer_after(&self, delay: Duration, ephemeral: bool) -> JoinHandle<()> {
+        let response = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if let Err(why) = response.defer(ephemeral).await {
+                error!("Couldn't defer a response: {}", why.to_string());
+            }
+        })
+    }
+
+    /// Edits the original response, or the first reply to a message command.\
+    /// Sends the original response if nothing has been sent yet.
+    pub async fn edit_original(&self, message: impl Into<Message>) -> Result<SentMessage, Error> {
+        let message = message.into();
+        let mut acknowledgement = self.acknowledgement.lock().await;
+        match *acknowledgement {
+            Acknowledgement::Pending => {
+                self.respond(
+                    &mut acknowledgement,
+                    InteractionResponseType::ChannelMessageWithSource,
+                    &message,
+                    false,
+                )
+                .await
+            }
+            Acknowledgement::Deferred { ephemeral } => {
+                self.fill(&mut acknowledgement, &message, ephemeral, ephemeral)
+                    .await
+            }
+            _ => self.edit(&mut acknowledgement, &message).await,
+        }
+    }
+
+    /// Sends a message in addition to the original response.\
+    /// Sends the original response instead if nothing has been sent yet, as follow-ups require one.
+    pub async fn followup(
+        &self,
+        message: impl Into<Message>,
+        ephemeral: bool,
+    ) -> Result<SentMessage, Error> {
+        let message = message.into();
+        let mut acknowledgement = self.acknowledgement.lock().await;
+        match *acknowledgement {
+            Acknowledgement::Pending => {
+                self.respond(
+                    &mut acknowledgement,
+                    InteractionResponseType::ChannelMessageWithSource,
+                    &message,
+                    ephemeral,
+                )
+                .await
+            }
+            _ => self.follow_up(&message, ephemeral).await,
+        }
+    }
+
+    /// Opens a modal, its submission is routed to the owner of its custom ID.\
+    /// Modals can only be opened in response to commands and components, not to messages or other modals,
+    /// and only before anything else is sent.
+    pub async fn open_modal(&self, modal: Modal) -> Result<(), Error> {
+        let mut acknowledgement = self.acknowledgement.lock().await;
+        if *acknowledgement != Acknowledgement::Pending {
+            return Err(serenity::Error::Other(ALREADY_ACKNOWLEDGED).into());
+        }
+        match self.sink.open_modal(&modal).await {
+            Ok(()) => *acknowledgement = Acknowledgement::Responded,
+            Err(why) => {
+                if is_already_acknowledged(&why) {
+                    *acknowledgement = Acknowledgement::Responded;
                 }
+                return Err(why.into());
             }
-            Response::Message {
-                context: _,
-                message: _,
-            } => {
-                // TODO:
+        }
+        Ok(())
+    }
+
+    async fn respond(
+        &self,
+        acknowledgement: &mut Acknowledgement,
+        kind: InteractionResponseType,
+        message: &Message,
+        ephemeral: bool,
+    ) -> Result<SentMessage, Error> {
+        match self.sink.respond(kind, message, ephemeral).await {
+            Ok(sent) => {
+                *acknowledgement = Acknowledgement::Responded;
+                Ok(self.sent(sent))
             }
+            // Acknowledged outside of this response, e.g. through `to_*`,
+            // so the message is sent the way it would have been after a response
+            Err(why) if is_already_acknowledged(&why) => {
+                *acknowledgement = Acknowledgement::Responded;
+                match kind {
+                    InteractionResponseType::UpdateMessage => {
+                        self.edit(acknowledgement, message).await
+                    }
+                    _ => self.follow_up(message, ephemeral).await,
+                }
+            }
+            Err(why) => Err(why.into()),
         }
     }
 
+    /// Fills in the placeholder of a deferred response, which is `deferred` ephemeral.\
+    /// A public placeholder is deleted before sending an ephemeral message,
+    /// as the message would be shown to everyone otherwise.
+    /// Messages with files or flags are sent as the first follow-up, which Discord puts in the placeholder.
+    async fn fill(
+        &self,
+        acknowledgement: &mut Acknowledgement,
+        message: &Message,
+        deferred: bool,
+        ephemeral: bool,
+    ) -> Result<SentMessage, Error> {
+        if ephemeral && !deferred {
+            // The first follow-up would fill in the placeholder, ignoring that it's ephemeral
+            self.sink.delete_sent(Sent::Original).await?;
+            *acknowledgement = Acknowledgement::Responded;
+            return self.follow_up(message, true).await;
+        }
+        // A public message stays ephemeral if the placeholder is, it can't be made public anymore
+        if message.has_files_or_flags() {
+            // Editing drops them, while the first follow-up fills in the placeholder with everything
+            let sent = self.follow_up(message, deferred).await?;
+            *acknowledgement = Acknowledgement::Responded;
+            return Ok(sent);
+        }
+        self.edit(acknowledgement, message).await
+    }
+
+    async fn edit(
+        &self,
+        acknowledgement: &mut Acknowledgement,
+        message: &Message,
+    ) -> Result<SentMessage, Error> {
+        let sent = self.sink.edit(message).await?;
+        *acknowledgement = Acknowledgement::Responded;
+        Ok(self.sent(sent))
+    }
+
+    async fn follow_up(&self, message: &Message, ephemeral: bool) -> Result<SentMessage, Error> {
+        Ok(self.sent(self.sink.follow_up(message, ephemeral).await?))
+    }
+
+    fn sent(&self, sent: Sent) -> SentMessage {
+        SentMessage {
+            sink: self.sink.clone(),
+            sent,
+            catalog: self.catalog.clone(),
+            state: self.state.clone(),
+        }
+    }
+
+    /// Checks if something has already been sent, or the response has been deferred.\
+    /// Typing in response to a message command doesn't count.
+    pub async fn is_acknowledged(&self) -> bool {
+        *self.acknowledgement.lock().await != Acknowledgement::Pending
+    }
+
     /// Checks if the issued command is an interaction.
     pub fn is_interaction_command(&self) -> bool {
-        if let Self::InteractionCommand {
-            context: _,
-            command: _,
-        } = self
-        {
-            true
-        } else {
-            false
-        }
+        self.origin == Origin::InteractionCommand
     }
     /// Checks if the command was issued from an ordinary message.
     pub fn is_message_command(&self) -> bool {
-        !self.is_interaction_command()
+        self.origin == Origin::Message
+    }
+    /// Checks if the response is to an interaction with a message component.
+    pub fn is_component(&self) -> bool {
+        self.origin == Origin::Component
+    }
+    /// Checks if the response is to a submitted modal.
+    pub fn is_modal(&self) -> bool {
+        self.origin == Origin::Modal
     }
 
     pub fn to_application_command_interaction(
         self,
     ) -> Option<(Context, ApplicationCommandInteraction)> {
-        if let Self::InteractionCommand { context, command } = self {
-            Some((context, command))
-        } else {
-            None
+        let (context, source) = self.discord?;
+        match &*source {
+            Source::InteractionCommand(command) => Some((context, command.clone())),
+            _ => None,
         }
     }
     pub fn to_message(self) -> Option<(Context, SerenityMessage)> {
-        if let Self::Message { context, message } = self {
-            Some((context, message))
-        } else {
-            None
+        let (context, source) = self.discord?;
+        match &*source {
+            Source::Message(message) => Some((context, message.clone())),
+            _ => None,
+        }
+    }
+    pub fn to_message_component_interaction(
+        self,
+    ) -> Option<(Context, MessageComponentInteraction)> {
+        let (context, source) = self.discord?;
+        match &*source {
+            Source::Component(component) => Some((context, *component.clone())),
+            _ => None,
         }
     }
+    pub fn to_modal_submit_interaction(self) -> Option<(Context, ModalSubmitInteraction)> {
+        let (context, source) = self.discord?;
+        match &*source {
+            Source::Modal(modal) => Some((context, *modal.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// Checks if Discord rejected a response because the interaction has already been acknowledged.
+fn is_already_acknowledged(why: &serenity::Error) -> bool {
+    match why {
+        serenity::Error::Http(http_error) => matches!(
+            &**http_error,
+            HttpError::UnsuccessfulRequest(response) if response.error.code == 40060
+        ),
+        _ => false,
+    }
+}
+
+/// Handle to a message sent by a [Response], be it the original response,
+/// a follow-up message or a reply to a message command.
+#[derive(Clone)]
+pub struct SentMessage {
+    sink: Arc<dyn Sink>,
+    sent: Sent,
+    catalog: Arc<Catalog>,
+    state: Arc<State>,
+}
+impl SentMessage {
+    /// Returns which of the delivered messages this is.
+    pub fn get_sent(&self) -> Sent {
+        self.sent
+    }
+
+    pub async fn edit(&self, message: impl Into<Message>) -> Result<(), Error> {
+        Ok(self.sink.edit_sent(self.sent, &message.into()).await?)
+    }
+
+    pub async fn delete(&self) -> Result<(), Error> {
+        Ok(self.sink.delete_sent(self.sent).await?)
+    }
+
+    /// Adds a reaction of the bot, ephemeral messages can't be reacted to.
+    pub async fn react(&self, reaction: impl Into<ReactionType>) -> Result<(), Error> {
+        Ok(self.sink.react(self.sent, reaction.into()).await?)
+    }
+
+    /// Waits up to `timeout` for someone to interact with a component of the message,
+    /// returning the interaction as a request and a response.\
+    /// The interaction is still routed to the owner of the component's custom ID as well,
+    /// so awaited components should have custom IDs no command or component handler owns.
+    pub async fn await_component(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<(Request, Response)>, Error> {
+        let interaction = self.sink.await_component(self.sent, timeout).await?;
+        Ok(interaction.map(|(req, res)| {
+            (
+                req.with_state(self.state.clone()),
+                res.with_catalog(self.catalog.clone())
+                    .with_state(self.state.clone()),
+            )
+        }))
+    }
 }
@@ -14,10 +14,15 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
-use framework::{command::Register, Command, Response};
-use once_cell::sync::Lazy;
+use std::{collections::BTreeMap, fs, io, path::Path, sync::Arc};
+
+use framework::{Command, CommandRegistry, Help};
 use serenity::{
-    model::prelude::application::command::Command as SerenityCommand, prelude::Context,
+    builder::{CreateApplicationCommand, CreateApplicationCommands},
+    http::HttpError,
+    json::{hashmap_to_json_map, Value},
+    model::prelude::{application::command::Command as SerenityCommand, ApplicationId, GuildId},
+    prelude::Context,
 };
 
 pub mod framework;
@@ -26,22 +31,357 @@ pub mod framework;
 pub mod ping;
 pub mod purge;
 
-pub static COMMANDS: Lazy<Vec<Box<dyn Command>>> = Lazy::new(|| {
-    let mut vector: Vec<Box<dyn Command>> = Vec::new();
-
+/// Builds the registry of commands and component handlers shipped with the bot.
+pub fn registry() -> Arc<CommandRegistry> {
+    let registry = Arc::new(CommandRegistry::new());
+    registry.add_in("Utility", Help::new(&registry));
     //? Commands go here
-    vector.push(Box::new(ping::Ping));
-    vector.push(Box::new(purge::Purge));
+    registry.add(ping::Ping);
+    registry.add_in("Moderation", purge::Purge);
+    //? Component handlers go here
+    registry
+}
+
+/// Registers commands in the registry, unless they haven't changed since the last registration
+/// recorded in the `cache` file.\
+/// When `development_guilds` aren't empty, commands available everywhere are registered in them
+/// instead of globally. Guilds commands were registered in last time, but aren't anymore,
+/// are cleared of them. `force` registers commands regardless of the cache.
+pub async fn register_if_changed(
+    context: &Context,
+    registry: &CommandRegistry,
+    application: ApplicationId,
+    development_guilds: &[GuildId],
+    cache: &Path,
+    force: bool,
+) -> serenity::Result<()> {
+    let targets = targets(&registry.commands(), development_guilds);
+    let fingerprint = format!("{}:{:016x}", application, fingerprint(&targets));
+
+    // The first line holds the fingerprint, the second one guilds commands were registered in
+    let cached = match fs::read_to_string(cache) {
+        Ok(cached) => cached,
+        Err(why) if why.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(why) => {
+            warn!(
+                "Couldn't read the registration cache `{}`: {}",
+                cache.display(),
+                why.to_string()
+            );
+            String::new()
+        }
+    };
+    let mut lines = cached.lines();
+    if !force && lines.next().map(str::trim) == Some(fingerprint.as_str()) {
+        info!("Commands haven't changed since the last registration, skipping it");
+        return Ok(());
+    }
+    let stale = stale(lines.next().unwrap_or_default(), &targets);
+
+    // Failing to register in one guild shouldn't prevent registering everywhere else
+    let mut result = Ok(());
+    for (guild, commands) in targets.iter() {
+        if let Err(why) = register(context, *guild, commands).await {
+            error!(
+                "Couldn't register commands {}: {}",
+                location(*guild),
+                why.to_string()
+            );
+            result = Err(why);
+        }
+    }
+    for guild in stale {
+        match register(context, Some(guild), &[]).await {
+            Ok(()) => {}
+            // The bot has left the guild since, which took its commands with it
+            Err(why) if is_inaccessible(&why) => warn!(
+                "Couldn't remove commands {}: {}",
+                location(Some(guild)),
+                why.to_string()
+            ),
+            Err(why) => {
+                error!(
+                    "Couldn't remove commands {}: {}",
+                    location(Some(guild)),
+                    why.to_string()
+                );
+                result = Err(why);
+            }
+        }
+    }
+    result?;
+
+    let guilds: Vec<String> = targets
+        .keys()
+        .flatten()
+        .map(|guild| guild.to_string())
+        .collect();
+    if let Err(why) = fs::write(cache, format!("{}\n{}\n", fingerprint, guilds.join(" "))) {
+        warn!(
+            "Couldn't write the registration cache `{}`: {}",
+            cache.display(),
+            why.to_string()
+        );
+    }
+    Ok(())
+}
+
+/// Overwrites application commands of the guild, or global ones if it's `None`, with the commands,
+/// logging which of them were created, updated or removed.
+pub async fn register(
+    context: &Context,
+    guild: Option<GuildId>,
+    commands: &[Arc<dyn Command>],
+) -> serenity::Result<()> {
+    let layouts = layouts(commands);
+    let set = |builder: &mut CreateApplicationCommands| {
+        for layout in layouts {
+            builder.add_application_command(layout);
+        }
+    };
+
+    let (existing, registered) = match guild {
+        Some(guild) => (
+            guild.get_application_commands(&context.http).await?,
+            guild
+                .set_application_commands(&context.http, |builder| {
+                    set(builder);
+                    builder
+                })
+                .await,
+        ),
+        None => (
+            SerenityCommand::get_global_application_commands(&context.http).await?,
+            SerenityCommand::set_global_application_commands(&context.http, |builder| {
+                set(builder);
+                builder
+            })
+            .await,
+        ),
+    };
+    let registered = registered.inspect_err(|why| log_rejected_commands(why, commands))?;
 
-    vector
-});
+    let location = location(guild);
+    for command in registered.iter() {
+        // Discord bumps the version of a command only if its definition has changed
+        match existing.iter().find(|old| old.name == command.name) {
+            None => info!("Created command `{}` {}", command.name, location),
+            Some(old) if old.version != command.version => {
+                info!("Updated command `{}` {}", command.name, location)
+            }
+            Some(_) => debug!("Command `{}` is up to date {}", command.name, location),
+        }
+    }
+    for old in existing.iter() {
+        if !registered.iter().any(|command| command.name == old.name) {
+            info!("Removed command `{}` {}", old.name, location);
+        }
+    }
+    Ok(())
+}
 
-pub async fn register(context: &Context) {
-    let _ = SerenityCommand::create_global_application_command(context.http.clone(), |commands| {
-        for command in COMMANDS.iter() {
-            command.register(commands);
+/// Groups commands by the guild they're registered in, `None` stands for global commands.\
+/// Global commands are always overwritten outside of development, so removed commands disappear.
+fn targets(
+    commands: &[Arc<dyn Command>],
+    development_guilds: &[GuildId],
+) -> BTreeMap<Option<GuildId>, Vec<Arc<dyn Command>>> {
+    let mut targets = BTreeMap::new();
+    if development_guilds.is_empty() {
+        targets.insert(None, Vec::new());
+    }
+    for command in commands {
+        let guilds = command.guilds();
+        let guilds = if !guilds.is_empty() {
+            guilds.into_iter().map(Some).collect()
+        } else if !development_guilds.is_empty() {
+            development_guilds.iter().copied().map(Some).collect()
+        } else {
+            vec![None]
+        };
+        for guild in guilds {
+            targets
+                .entry(guild)
+                .or_insert_with(Vec::new)
+                .push(command.clone());
         }
-        commands
-    })
-    .await;
+    }
+    targets
+}
+
+/// Picks guilds out of the cached ones that commands aren't registered in anymore.
+fn stale(cached: &str, targets: &BTreeMap<Option<GuildId>, Vec<Arc<dyn Command>>>) -> Vec<GuildId> {
+    cached
+        .split_whitespace()
+        .filter_map(|guild| guild.parse().ok().map(GuildId))
+        .filter(|guild| !targets.contains_key(&Some(*guild)))
+        .collect()
+}
+
+/// Checks if Discord refused a request because the bot has no access to the guild.
+fn is_inaccessible(why: &serenity::Error) -> bool {
+    match why {
+        serenity::Error::Http(http_error) => matches!(
+            http_error.as_ref(),
+            HttpError::UnsuccessfulRequest(response)
+                if matches!(response.status_code.as_u16(), 403 | 404)
+        ),
+        _ => false,
+    }
+}
+
+/// Describes where commands are registered for logging.
+fn location(guild: Option<GuildId>) -> String {
+    match guild {
+        Some(guild) => format!("in guild `{}`", guild),
+        None => String::from("globally"),
+    }
+}
+
+/// Builds the layout of every command, in the order they're registered in.
+fn layouts(commands: &[Arc<dyn Command>]) -> Vec<CreateApplicationCommand> {
+    commands
+        .iter()
+        .map(|command| {
+            let mut layout = CreateApplicationCommand::default();
+            command.layout(&mut layout);
+            layout
+        })
+        .collect()
+}
+
+/// Hashes serialized layouts and where they're registered with 64-bit FNV-1a, which unlike
+/// the standard library hasher is stable across builds.\
+/// Keys of JSON objects are serialized in sorted order, so the result doesn't depend on the order
+/// options were set in.
+fn fingerprint(targets: &BTreeMap<Option<GuildId>, Vec<Arc<dyn Command>>>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (guild, commands) in targets {
+        let mut serialized = location(*guild);
+        for layout in layouts(commands) {
+            serialized.push('\n');
+            serialized.push_str(&Value::Object(hashmap_to_json_map(layout.0)).to_string());
+        }
+        for byte in serialized.bytes().chain(std::iter::once(b'\n')) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+/// Logs the reason Discord rejected each invalid command, paths of errors start with the index of the command.
+fn log_rejected_commands(why: &serenity::Error, commands: &[Arc<dyn Command>]) {
+    let response = match why {
+        serenity::Error::Http(http_error) => match http_error.as_ref() {
+            HttpError::UnsuccessfulRequest(response) => response,
+            _ => return,
+        },
+        _ => return,
+    };
+    for error in response.error.errors.iter() {
+        let (index, path) = error.path.split_once('.').unwrap_or((&error.path, ""));
+        let name = index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| commands.get(index))
+            .map(|command| command.name())
+            .unwrap_or("?");
+        error!(
+            "Discord rejected command `{}` at `{}`: {}",
+            name, path, error.message
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use framework::{command, CommandResult, Request, Response};
+
+    /// Echo the message
+    #[command]
+    async fn echo(_req: Request, _res: Response) -> CommandResult {
+        Ok(())
+    }
+
+    /// Repeat the message
+    #[command(name = "echo")]
+    async fn repeat(_req: Request, _res: Response) -> CommandResult {
+        Ok(())
+    }
+
+    /// Moderate the server
+    #[command(guilds = [3])]
+    async fn moderate(_req: Request, _res: Response) -> CommandResult {
+        Ok(())
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        // FNV-1a of `globally\n`, changing it makes every deployment register commands again
+        assert_eq!(fingerprint(&targets(&[], &[])), 0xa581407cd2c4aae5);
+
+        let commands: Vec<Arc<dyn Command>> = vec![Arc::new(Echo), Arc::new(ping::Ping)];
+        assert_eq!(
+            fingerprint(&targets(&commands, &[])),
+            fingerprint(&targets(&commands, &[]))
+        );
+    }
+
+    #[test]
+    fn fingerprint_changes_with_layouts_and_guilds() {
+        let echo: Vec<Arc<dyn Command>> = vec![Arc::new(Echo)];
+        let repeat: Vec<Arc<dyn Command>> = vec![Arc::new(Repeat)];
+        let fingerprints = [
+            fingerprint(&targets(&echo, &[])),
+            fingerprint(&targets(&repeat, &[])),
+            fingerprint(&targets(&echo, &[GuildId(1)])),
+            fingerprint(&targets(&echo, &[GuildId(2)])),
+        ];
+
+        for (index, fingerprint) in fingerprints.iter().enumerate() {
+            assert!(!fingerprints[index + 1..].contains(fingerprint));
+        }
+    }
+
+    #[test]
+    fn commands_go_to_development_guilds() {
+        let commands: Vec<Arc<dyn Command>> = vec![Arc::new(Echo), Arc::new(Moderate)];
+        let targets = targets(&commands, &[GuildId(1), GuildId(2)]);
+        let names = |guild| {
+            targets[&guild]
+                .iter()
+                .map(|command| command.name())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(!targets.contains_key(&None));
+        assert_eq!(names(Some(GuildId(1))), ["echo"]);
+        assert_eq!(names(Some(GuildId(2))), ["echo"]);
+        assert_eq!(names(Some(GuildId(3))), ["moderate"]);
+    }
+
+    #[test]
+    fn commands_go_globally_outside_of_development() {
+        let commands: Vec<Arc<dyn Command>> = vec![Arc::new(Echo), Arc::new(Moderate)];
+        let targets = targets(&commands, &[]);
+
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[&None][0].name(), "echo");
+        assert_eq!(targets[&Some(GuildId(3))][0].name(), "moderate");
+        // Global commands are overwritten even when there are none left
+        assert!(super::targets(&[], &[])[&None].is_empty());
+    }
+
+    #[test]
+    fn guilds_dropped_from_targets_are_stale() {
+        let commands: Vec<Arc<dyn Command>> = vec![Arc::new(Echo), Arc::new(Moderate)];
+        let targets = targets(&commands, &[GuildId(1)]);
+
+        assert_eq!(stale("1 2 3 4", &targets), [GuildId(2), GuildId(4)]);
+        assert!(stale("", &targets).is_empty());
+        // Garbage left in the cache is ignored
+        assert_eq!(stale("x 2", &targets), [GuildId(2)]);
+    }
 }
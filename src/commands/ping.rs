@@ -14,7 +14,7 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
-use super::{framework::Request, Command, Response};
+use super::framework::{Command, CommandResult, Request, Response};
 use serenity::async_trait;
 
 pub struct Ping;
@@ -27,7 +27,9 @@ impl Command for Ping {
         "Ping? Pong!"
     }
 
-    async fn run(&self, _req: Request, res: Response) {
-        res.send_ephemeral_message("Pong!", true).await;
+    async fn run(&self, _req: Request, res: Response) -> CommandResult {
+        res.send_ephemeral_message(res.render("ping-response", None), true)
+            .await?;
+        Ok(())
     }
 }
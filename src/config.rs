@@ -16,6 +16,7 @@
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
 use log::LevelFilter;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Deserialize, Serialize, Default, Clone, Copy, Debug)]
 pub struct LogLevels {
@@ -27,16 +28,7 @@ pub struct LogLevels {
 }
 impl LogLevels {
     pub fn is_disabled(&self) -> bool {
-        if self.error == false
-            && self.warn == false
-            && self.info == false
-            && self.debug == false
-            && self.trace == false
-        {
-            true
-        } else {
-            false
-        }
+        !self.error && !self.warn && !self.info && !self.debug && !self.trace
     }
 
     pub fn to_level_filter(self) -> LevelFilter {
@@ -87,14 +79,14 @@ impl Default for Log {
     }
 }
 
-#[derive(Deserialize, Serialize, Default)]
+#[derive(Deserialize, Serialize, Default, Clone)]
 pub struct Intents {
     pub presence: bool,
     pub server_members: bool,
     pub message_content: bool,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Discord {
     pub token: String,
     pub intents: Intents,
@@ -109,8 +101,57 @@ impl Default for Discord {
         }
     }
 }
-#[derive(Default, Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Commands {
+    /// Prefix which message commands have to start with.
+    pub prefix: String,
+    /// Prefixes which override the global one in specific guilds, keyed by guild ID.
+    pub guild_prefixes: HashMap<String, String>,
+    /// Milliseconds after which a command which hasn't responded yet is deferred, `0` disables it.\
+    /// Discord drops interactions which weren't responded to within 3 seconds.
+    pub defer_after: u64,
+    /// Roles which bypass cooldowns of commands, by ID.
+    pub cooldown_exempt_roles: Vec<u64>,
+    /// Whether owners of the bot application bypass cooldowns of commands.
+    pub cooldown_exempt_owners: bool,
+    /// Guilds which commands are registered in instead of globally, by ID.\
+    /// Guild commands update instantly, unlike global ones.
+    pub development_guilds: Vec<u64>,
+    /// Directory with Fluent files translating responses, one subdirectory per locale.
+    pub locales: String,
+    /// Locale responses are rendered in when the user's one has no translation.
+    pub default_locale: String,
+    /// File storing a hash of the last registered commands, so unchanged ones aren't registered again,
+    /// and guilds they were registered in, so they can be removed from guilds they're no longer registered in.
+    pub registration_cache: String,
+}
+impl Commands {
+    /// Returns the prefix used in the guild, or the global one outside of guilds.
+    pub fn prefix(&self, guild: Option<u64>) -> &str {
+        guild
+            .and_then(|guild| self.guild_prefixes.get(&guild.to_string()))
+            .unwrap_or(&self.prefix)
+    }
+}
+impl Default for Commands {
+    fn default() -> Self {
+        Self {
+            prefix: String::from("!"),
+            guild_prefixes: HashMap::new(),
+            defer_after: 2000,
+            cooldown_exempt_roles: Vec::new(),
+            cooldown_exempt_owners: true,
+            development_guilds: Vec::new(),
+            locales: String::from("locales"),
+            default_locale: String::from("en-US"),
+            registration_cache: String::from(".eternacore-commands"),
+        }
+    }
+}
+
+#[derive(Default, Deserialize, Serialize, Clone)]
 pub struct Config {
     pub discord: Discord,
+    pub commands: Commands,
     pub log: Log,
 }
@@ -14,44 +14,527 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
-use crate::commands::{
-    framework::{Request, Response},
-    register, COMMANDS,
+use std::{
+    future::Future,
+    path::Path,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    commands::{
+        framework::{
+            autocomplete::MAX_CHOICES, checks, component, cooldown::Cooldowns, parser::ParseError,
+            Catalog, Command, CommandRegistry, CommandResult, DefaultErrorHandler, Error,
+            ErrorHandler, Middleware, Request, Response, State,
+        },
+        register_if_changed,
+    },
+    config,
 };
 use serenity::{
     async_trait,
-    model::application::interaction::Interaction,
+    builder::CreateApplicationCommand,
+    model::application::{command::CommandType, interaction::Interaction},
     model::prelude::*,
     prelude::{Context, EventHandler},
 };
+use tokio::task::JoinHandle;
+
+/// Parses the arguments of a message command into its request.
+type Arguments = Pin<Box<dyn Future<Output = Result<Request, ParseError>> + Send>>;
 
-pub struct Handler;
+pub struct Handler {
+    commands: config::Commands,
+    registry: Arc<CommandRegistry>,
+    error_handler: Box<dyn ErrorHandler>,
+    middleware: Vec<Box<dyn Middleware>>,
+    cooldowns: Arc<Cooldowns>,
+    catalog: Arc<Catalog>,
+    state: Arc<State>,
+    force_register: bool,
+    registered: AtomicBool,
+}
+impl Handler {
+    pub fn new(commands: config::Commands, registry: Arc<CommandRegistry>) -> Self {
+        let cooldowns = Cooldowns::new(
+            commands
+                .cooldown_exempt_roles
+                .iter()
+                .map(|&role| RoleId(role))
+                .collect(),
+            commands.cooldown_exempt_owners,
+        );
+        Self {
+            commands,
+            registry,
+            error_handler: Box::new(DefaultErrorHandler),
+            middleware: Vec::new(),
+            cooldowns: Arc::new(cooldowns),
+            catalog: Arc::new(Catalog::new()),
+            state: Arc::new(State::new()),
+            force_register: false,
+            registered: AtomicBool::new(false),
+        }
+    }
+
+    /// Replaces the catalog responses are rendered from.
+    pub fn catalog(mut self, catalog: Catalog) -> Self {
+        self.catalog = Arc::new(catalog);
+        self
+    }
+
+    /// Replaces the shared application state reachable from every request.
+    pub fn state(mut self, state: State) -> Self {
+        self.state = Arc::new(state);
+        self
+    }
+
+    /// Registers commands even if they haven't changed since the last registration.
+    pub fn force_register(mut self, force_register: bool) -> Self {
+        self.force_register = force_register;
+        self
+    }
+
+    /// Replaces the handler reporting failed commands, components and modals.
+    pub fn error_handler(mut self, error_handler: impl ErrorHandler) -> Self {
+        self.error_handler = Box::new(error_handler);
+        self
+    }
+
+    /// Adds a middleware ran around every command, in the order they were added.
+    pub fn middleware(mut self, middleware: impl Middleware) -> Self {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Passes the command through middleware, parses `arguments` of message commands,
+    /// evaluates checks declared on it, takes a use of its cooldown, defers its response if needed and runs it.
+    async fn run(
+        &self,
+        command: Arc<dyn Command>,
+        request: Request,
+        response: Response,
+        arguments: Option<Arguments>,
+    ) {
+        if !self.registry.is_enabled(command.name()) {
+            return self
+                .error_handler
+                .handle(command.name(), Error::Disabled, response)
+                .await;
+        }
+        for middleware in self.middleware.iter() {
+            if let Err(why) = middleware.before(command.as_ref(), &request).await {
+                self.error_handler
+                    .handle(command.name(), why, response)
+                    .await;
+                return;
+            }
+        }
+
+        let cooldowns = self.cooldowns.clone();
+        let defer_after = self.commands.defer_after;
+        let invocation = request.clone();
+        let reporter = response.clone();
+        let started = Instant::now();
+        let defined_command = command.clone();
+        let result = spawn(async move {
+            let command = defined_command;
+            let request = match arguments {
+                Some(arguments) => match arguments.await {
+                    Ok(request) => request,
+                    // Malformed arguments are only explained to those who could use the command,
+                    // and take a use of its cooldown like any other invocation
+                    Err(why) => {
+                        guard(command.as_ref(), &request, &cooldowns).await?;
+                        return Err(why.into());
+                    }
+                },
+                None => request,
+            };
+            guard(command.as_ref(), &request, &cooldowns).await?;
+            // Rejected commands are answered right away instead of replacing a placeholder
+            let timer = defer(command.as_ref(), &response, defer_after).await?;
+            let result = command.run(request, response).await;
+            // Commands which finished without responding aren't left with a placeholder
+            if let Some(timer) = timer {
+                timer.abort();
+            }
+            result
+        })
+        .await;
+        let elapsed = started.elapsed();
+
+        for middleware in self.middleware.iter() {
+            middleware
+                .after(command.as_ref(), &invocation, &result, elapsed)
+                .await;
+        }
+        if let Err(why) = result {
+            self.error_handler
+                .handle(command.name(), why, reporter)
+                .await;
+        }
+    }
+
+    /// Runs a component or modal handler, reporting its failure.
+    async fn execute(
+        &self,
+        name: &str,
+        response: Response,
+        future: impl Future<Output = CommandResult> + Send + 'static,
+    ) {
+        if let Err(why) = spawn(future).await {
+            self.error_handler.handle(name, why, response).await;
+        }
+    }
+}
+
+/// Defers slow commands right away and the rest once they exceed `defer_after` milliseconds,
+/// returns the timer of the latter.
+async fn defer(
+    command: &dyn Command,
+    response: &Response,
+    defer_after: u64,
+) -> Result<Option<JoinHandle<()>>, Error> {
+    if command.slow() {
+        response.defer(command.ephemeral()).await?;
+        Ok(None)
+    } else if defer_after > 0 {
+        Ok(Some(response.defer_after(
+            Duration::from_millis(defer_after),
+            command.ephemeral(),
+        )))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Evaluates checks and takes a use of the cooldown of the command
+/// and of every subcommand on the way to the invoked one.
+async fn guard(command: &dyn Command, request: &Request, cooldowns: &Cooldowns) -> CommandResult {
+    let mut defined_command = command;
+    let mut invoked = request.command();
+    loop {
+        let request = request.clone().with_command(invoked.clone());
+        checks::evaluate(defined_command, &request).await?;
+        if let Some(cooldown) = defined_command.cooldown() {
+            // Subcommands are counted separately, e.g. as `mod warn`
+            cooldowns
+                .hit(&invoked.path().join(" "), &cooldown, &request)
+                .await?;
+        }
+
+        match invoked.subcommand().and_then(|subcommand| {
+            let defined_subcommand = defined_command.subcommand(&subcommand.name())?;
+            Some((subcommand, defined_subcommand))
+        }) {
+            Some((subcommand, defined_subcommand)) => {
+                invoked = subcommand;
+                defined_command = defined_subcommand;
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Evaluates checks of the command owning a component or modal and of every subcommand on `path`,
+/// so they can't be used by someone who can't use the command itself.
+async fn authorize(command: &dyn Command, path: &[String], request: &Request) -> CommandResult {
+    let mut defined_command = command;
+    checks::evaluate(defined_command, request).await?;
+    for name in path.iter().skip(1) {
+        match defined_command.subcommand(name) {
+            Some(defined_subcommand) => {
+                defined_command = defined_subcommand;
+                checks::evaluate(defined_command, request).await?;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+/// Runs a handler in its own task so panics are reported as internal errors.
+async fn spawn(future: impl Future<Output = CommandResult> + Send + 'static) -> CommandResult {
+    match tokio::spawn(future).await {
+        Ok(result) => result,
+        Err(why) => Err(Error::internal(why)),
+    }
+}
 
 #[async_trait]
 impl EventHandler for Handler {
-    async fn ready(&self, context: Context, _ready: Ready) {
-        register(&context).await;
+    async fn ready(&self, context: Context, ready: Ready) {
+        // Every shard becomes ready on its own and again after reconnecting,
+        // but commands only have to be registered once per process
+        if self.registered.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let development_guilds: Vec<GuildId> = self
+            .commands
+            .development_guilds
+            .iter()
+            .map(|&guild| GuildId(guild))
+            .collect();
+        if let Err(why) = register_if_changed(
+            &context,
+            &self.registry,
+            ready.application.id,
+            &development_guilds,
+            Path::new(&self.commands.registration_cache),
+            self.force_register,
+        )
+        .await
+        {
+            error!("Couldn't register commands: {}", why.to_string());
+            // Let the next shard to become ready try again
+            self.registered.store(false, Ordering::SeqCst);
+        }
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::ApplicationCommand(command) = interaction {
-            for defined_command in COMMANDS.iter() {
-                if command.data.name == defined_command.name().to_string() {
-                    defined_command
-                        .run(
-                            Request::from_command_data_option_slice(
-                                ctx.http.clone(),
-                                command.channel_id,
-                                &command.data,
-                            ),
-                            Response::from_interaction_command(ctx, command),
-                        )
-                        .await;
-                    break;
+        match interaction {
+            Interaction::ApplicationCommand(command) => {
+                let defined_command = match self.registry.get(&command.data.name) {
+                    Some(defined_command) => defined_command,
+                    None => return warn!("Unknown command `{}`", command.data.name),
+                };
+                let request = Request::from_interaction_command(ctx.http.clone(), &command)
+                    .with_state(self.state.clone());
+                let response = Response::from_interaction_command(ctx, command)
+                    .with_catalog(self.catalog.clone())
+                    .with_state(self.state.clone());
+                self.run(defined_command, request, response, None).await;
+            }
+            Interaction::Autocomplete(autocomplete) => {
+                let defined_command = match self.registry.get(&autocomplete.data.name) {
+                    Some(defined_command) => defined_command,
+                    None => return,
+                };
+                let request = Request::from_autocomplete(ctx.http.clone(), &autocomplete)
+                    .with_state(self.state.clone());
+                let (option, value) = match request.command().focused() {
+                    Some(focused) => focused,
+                    None => return,
+                };
+                let choices = defined_command.autocomplete(request, &option, &value).await;
+
+                if let Err(why) = autocomplete
+                    .create_autocomplete_response(&ctx.http, |response| {
+                        for choice in choices.into_iter().take(MAX_CHOICES) {
+                            choice.add_to(response);
+                        }
+                        response
+                    })
+                    .await
+                {
+                    error!(
+                        "Couldn't respond to an autocomplete interaction `{}`: {}",
+                        autocomplete.id,
+                        why.to_string()
+                    );
+                }
+            }
+            Interaction::MessageComponent(interaction) => {
+                let custom_id = interaction.data.custom_id.clone();
+                let (namespace, _) = component::split_custom_id(&custom_id);
+                let path = component::decode_path(namespace);
+                let owner = path[0].as_str();
+                let request = Request::from_component(ctx.http.clone(), &interaction)
+                    .with_state(self.state.clone());
+                let response = Response::from_component(ctx, interaction)
+                    .with_catalog(self.catalog.clone())
+                    .with_state(self.state.clone());
+
+                if let Some(defined_command) = self.registry.get(owner) {
+                    if !self.registry.is_enabled(defined_command.name()) {
+                        return self
+                            .error_handler
+                            .handle(&custom_id, Error::Disabled, response)
+                            .await;
+                    }
+                    let res = response.clone();
+                    let path = path.clone();
+                    self.execute(&custom_id, response, async move {
+                        authorize(defined_command.as_ref(), &path, &request).await?;
+                        defined_command.component(request, res).await
+                    })
+                    .await;
+                } else if let Some(handler) = self.registry.get_component_handler(owner) {
+                    let res = response.clone();
+                    self.execute(&custom_id, response, async move {
+                        handler.run(request, res).await
+                    })
+                    .await;
+                } else {
+                    warn!("No handler for component `{}`", custom_id);
+                }
+            }
+            Interaction::ModalSubmit(interaction) => {
+                let custom_id = interaction.data.custom_id.clone();
+                let (namespace, _) = component::split_custom_id(&custom_id);
+                let path = component::decode_path(namespace);
+                let owner = path[0].as_str();
+                let request = Request::from_modal_submit(ctx.http.clone(), &interaction)
+                    .with_state(self.state.clone());
+                let response = Response::from_modal_submit(ctx, interaction)
+                    .with_catalog(self.catalog.clone())
+                    .with_state(self.state.clone());
+
+                if let Some(defined_command) = self.registry.get(owner) {
+                    if !self.registry.is_enabled(defined_command.name()) {
+                        return self
+                            .error_handler
+                            .handle(&custom_id, Error::Disabled, response)
+                            .await;
+                    }
+                    let res = response.clone();
+                    let path = path.clone();
+                    self.execute(&custom_id, response, async move {
+                        authorize(defined_command.as_ref(), &path, &request).await?;
+                        defined_command.modal(request, res).await
+                    })
+                    .await;
+                } else if let Some(handler) = self.registry.get_component_handler(owner) {
+                    let res = response.clone();
+                    self.execute(&custom_id, response, async move {
+                        handler.modal(request, res).await
+                    })
+                    .await;
+                } else {
+                    warn!("No handler for modal `{}`", custom_id);
                 }
             }
+            _ => {}
+        }
+    }
+
+    async fn message(&self, ctx: Context, new_message: Message) {
+        if new_message.author.bot {
+            return;
         }
+        let prefix = self
+            .commands
+            .prefix(new_message.guild_id.map(|guild| guild.0));
+        let input = match new_message.content.strip_prefix(prefix) {
+            Some(input) if !prefix.is_empty() => input,
+            _ => return,
+        };
+        let (name, input) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let defined_command = match self.registry.get(name) {
+            Some(defined_command) => defined_command,
+            None => return,
+        };
+        // Context menu commands can only be invoked by right-clicking
+        if defined_command.kind() != CommandType::ChatInput {
+            return;
+        }
+        // Commands registered only in some guilds don't exist anywhere else
+        let guilds = defined_command.guilds();
+        if !guilds.is_empty()
+            && !new_message
+                .guild_id
+                .is_some_and(|guild| guilds.contains(&guild))
+        {
+            return;
+        }
+
+        let request = Request::from_message(&ctx, &new_message, prefix, defined_command.name())
+            .with_state(self.state.clone());
+        let mut layout = CreateApplicationCommand::default();
+        defined_command.layout(&mut layout);
+        let arguments: Arguments = {
+            let (ctx, message, request) = (ctx.clone(), new_message.clone(), request.clone());
+            let input = input.to_string();
+            Box::pin(async move {
+                request
+                    .parse_arguments(&ctx, &message, &layout, &input)
+                    .await
+            })
+        };
+        let response = Response::from_message(ctx, new_message)
+            .with_catalog(self.catalog.clone())
+            .with_state(self.state.clone());
+        self.run(defined_command, request, response, Some(arguments))
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+    use crate::commands::{
+        framework::{Origin, Recorder},
+        purge::Purge,
+    };
+
+    /// Silences everyone, like a blacklist containing every user.
+    struct Blacklist;
+    #[async_trait]
+    impl Middleware for Blacklist {
+        async fn before(&self, _command: &dyn Command, _req: &Request) -> CommandResult {
+            Err(Error::Cancelled)
+        }
+    }
+
+    fn registry() -> Arc<CommandRegistry> {
+        let registry = Arc::new(CommandRegistry::new());
+        registry.add(Purge);
+        registry
+    }
+
+    /// Arguments which fail to parse, `parsed` tells if parsing was attempted.
+    fn malformed(parsed: Arc<AtomicBool>) -> Arguments {
+        Box::pin(async move {
+            parsed.store(true, Ordering::SeqCst);
+            Err(ParseError::TrailingInput(String::from("abc")))
+        })
+    }
+
+    #[tokio::test]
+    async fn blacklisted_users_get_no_reply_to_malformed_commands() {
+        let handler = Handler::new(config::Commands::default(), registry()).middleware(Blacklist);
+        let recorder = Arc::new(Recorder::new());
+        let parsed = Arc::new(AtomicBool::new(false));
+
+        handler
+            .run(
+                handler.registry.get("purge").unwrap(),
+                Request::builder("purge").build(),
+                Response::from_sink(recorder.clone(), Origin::Message),
+                Some(malformed(parsed.clone())),
+            )
+            .await;
+
+        assert!(recorder.recorded().is_empty());
+        assert!(!parsed.load(Ordering::SeqCst));
     }
 
-    async fn message(&self, _context: Context, _new_message: Message) {}
+    #[tokio::test]
+    async fn checks_come_before_malformed_arguments() {
+        let handler = Handler::new(config::Commands::default(), registry());
+        let recorder = Arc::new(Recorder::new());
+
+        // Purge is guild only and the request comes from direct messages
+        handler
+            .run(
+                handler.registry.get("purge").unwrap(),
+                Request::builder("purge").build(),
+                Response::from_sink(recorder.clone(), Origin::Message),
+                Some(malformed(Arc::new(AtomicBool::new(false)))),
+            )
+            .await;
+
+        assert_eq!(
+            recorder.contents(),
+            ["This command can only be used in servers."]
+        );
+    }
 }
@@ -16,6 +16,8 @@
 // along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
 #[macro_use]
 extern crate serenity;
+// Lets code generated by `eternacore-macros` refer to this crate by name from inside of it
+extern crate self as eternacore;
 #[macro_use]
 extern crate log;
 
@@ -80,13 +80,8 @@ impl Logger {
 
 impl Log for Logger {
     fn enabled(&self, metadata: &log::Metadata) -> bool {
-        if (!self.0.levels.is_disabled() && self.0.max_level != LevelFilter::Off)
+        (!self.0.levels.is_disabled() && self.0.max_level != LevelFilter::Off)
             || metadata.level().to_level_filter() <= self.0.max_level
-        {
-            true
-        } else {
-            false
-        }
     }
     fn log(&self, record: &log::Record) {
         let target_max_width = TARGET_MAX_WIDTH.load(Ordering::Relaxed);
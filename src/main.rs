@@ -8,6 +8,10 @@ use figment::{
 use serenity::{gateway::GatewayError, prelude::GatewayIntents, Client};
 use std::{env, process};
 
+use eternacore::commands::{
+    self,
+    framework::{Catalog, State},
+};
 use eternacore::config::Config;
 use eternacore::handler::Handler;
 
@@ -44,7 +48,7 @@ async fn main() {
     // We can safely unwrap, because the only time it will
     // throw an [error](https://docs.rs/log/latest/log/struct.SetLoggerError.html)
     // is if a logger was already set
-    logger::Logger::from_config(config.log).unwrap();
+    logger::Logger::from_config(config.log.clone()).unwrap();
     info!(
         "Eternacore Discord Bot Revision {}",
         env!("CARGO_PKG_VERSION")
@@ -61,8 +65,26 @@ async fn main() {
         intents |= GatewayIntents::GUILD_MEMBERS;
     }
 
+    let catalog = match Catalog::load(&config.commands.locales, &config.commands.default_locale) {
+        Ok(catalog) => catalog,
+        Err(why) => {
+            error!("Couldn't load translations: {}", why);
+            process::exit(1);
+        }
+    };
+    let registry = commands::registry();
+    // Commands reach these through `Request::state`
+    let state = State::new()
+        .with(config.clone())
+        .with_shared(registry.clone());
+    let force_register = env::args().any(|argument| argument == "--force-register");
     let mut client = match Client::builder(&config.discord.token, intents)
-        .event_handler(Handler)
+        .event_handler(
+            Handler::new(config.commands, registry)
+                .catalog(catalog)
+                .state(state)
+                .force_register(force_register),
+        )
         .await
     {
         Ok(client) => client,
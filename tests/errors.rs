@@ -0,0 +1,67 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{fs, sync::Arc, time::Duration};
+
+use eternacore::commands::framework::{
+    ArgumentError, Catalog, DefaultErrorHandler, Error, ErrorHandler, Origin, Recorder, Response,
+};
+
+#[tokio::test]
+async fn errors_are_rendered_in_the_users_locale() {
+    let directory = std::env::temp_dir().join(format!("eternacore-errors-{}", std::process::id()));
+    fs::create_dir_all(directory.join("de")).unwrap();
+    fs::write(
+        directory.join("de/eternacore.ftl"),
+        "error-guild-only = Dieser Befehl kann nur auf Servern verwendet werden.\n",
+    )
+    .unwrap();
+    let catalog = Arc::new(Catalog::load(&directory, "en-US").unwrap());
+    fs::remove_dir_all(&directory).unwrap();
+
+    let recorder = Arc::new(Recorder::new());
+    let respond = |locale: &str| {
+        Response::from_sink(recorder.clone(), Origin::InteractionCommand)
+            .with_catalog(catalog.clone())
+            .with_locale(locale)
+    };
+    DefaultErrorHandler
+        .handle("ping", Error::GuildOnly, respond("de"))
+        .await;
+    DefaultErrorHandler
+        .handle(
+            "ping",
+            Error::Cooldown(Duration::from_millis(2500)),
+            respond("de"),
+        )
+        .await;
+    DefaultErrorHandler
+        .handle(
+            "purge",
+            ArgumentError::Missing(String::from("number")).into(),
+            respond("en-US"),
+        )
+        .await;
+
+    assert_eq!(
+        recorder.contents(),
+        [
+            "Dieser Befehl kann nur auf Servern verwendet werden.",
+            "This command is on cooldown, try again in 3s.",
+            "Missing required argument `number`.",
+        ]
+    );
+}
@@ -0,0 +1,103 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use eternacore::commands::{
+    framework::{command, Command, CommandResult, Request, Response},
+    purge::Purge,
+};
+use serenity::{
+    builder::CreateApplicationCommand,
+    json::{hashmap_to_json_map, json, Value},
+};
+
+/// Schedule a reminder
+#[command]
+async fn remind(
+    _req: Request,
+    _res: Response,
+    #[option(description = "When to remind", choices = ["today", "tomorrow"])] when: String,
+    #[option(description = "Hours to wait", min = 0.5, max = 48.0)] hours: f64,
+    #[option(description = "Note to attach", min_length = 1, max_length = 100)] note: Option<
+        String,
+    >,
+) -> CommandResult {
+    let _ = (when, hours, note);
+    Ok(())
+}
+
+fn layout(command: &dyn Command) -> Value {
+    let mut layout = CreateApplicationCommand::default();
+    command.layout(&mut layout);
+    Value::Object(hashmap_to_json_map(layout.0))
+}
+
+#[test]
+fn purge_declares_its_option() {
+    let layout = layout(&Purge);
+
+    assert_eq!(layout["name"], "purge");
+    assert_eq!(layout["description"], "Bulk delete up to 100 messages");
+    assert_eq!(
+        layout["options"],
+        json!([{
+            "type": 4,
+            "name": "number",
+            "description": "Number of messages to delete",
+            "required": true,
+            "min_value": 1,
+            "max_value": 100,
+        }])
+    );
+}
+
+#[test]
+fn options_declare_choices_bounds_and_optionality() {
+    let layout = layout(&Remind);
+
+    assert_eq!(layout["name"], "remind");
+    assert_eq!(layout["description"], "Schedule a reminder");
+    assert_eq!(
+        layout["options"],
+        json!([
+            {
+                "type": 3,
+                "name": "when",
+                "description": "When to remind",
+                "required": true,
+                "choices": [
+                    { "name": "today", "value": "today" },
+                    { "name": "tomorrow", "value": "tomorrow" },
+                ],
+            },
+            {
+                "type": 10,
+                "name": "hours",
+                "description": "Hours to wait",
+                "required": true,
+                "min_value": 0.5,
+                "max_value": 48.0,
+            },
+            {
+                "type": 3,
+                "name": "note",
+                "description": "Note to attach",
+                "required": false,
+                "min_length": 1,
+                "max_length": 100,
+            },
+        ])
+    );
+}
@@ -0,0 +1,78 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    sync::Arc,
+    thread,
+};
+
+use eternacore::commands::{
+    framework::{Command, Origin, Recorder, Request, Response},
+    purge::Purge,
+};
+use serenity::{
+    http::HttpBuilder, model::prelude::interaction::application_command::CommandDataOptionValue,
+};
+
+/// Serves a single request with `body`, returns the address of the server and the request it got.
+fn serve_once(body: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}/", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            let read = stream.read(&mut buffer).unwrap();
+            request.extend_from_slice(&buffer[..read]);
+        }
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        String::from_utf8_lossy(&request).to_string()
+    });
+    (address, server)
+}
+
+#[tokio::test]
+async fn purging_an_empty_channel_replies_after_fetching() {
+    let (address, server) = serve_once("[]");
+    let http = HttpBuilder::new("")
+        .proxy(address)
+        .unwrap()
+        .ratelimiter_disabled(true)
+        .build();
+    let recorder = Arc::new(Recorder::new());
+    let req = Request::builder("purge")
+        .option("number", CommandDataOptionValue::Integer(5))
+        .http(Arc::new(http))
+        .build();
+    let res = Response::from_sink(recorder.clone(), Origin::InteractionCommand);
+
+    Purge.run(req, res).await.unwrap();
+
+    assert!(server
+        .join()
+        .unwrap()
+        .starts_with("GET /api/v10/channels/0/messages?limit=5 "));
+    assert_eq!(recorder.contents(), ["Nothing left to delete."]);
+}
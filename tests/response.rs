@@ -0,0 +1,129 @@
+// Copyright (C) 2023 Egor Poleshko
+//
+// This file is part of Eternacore.
+//
+// Eternacore is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Eternacore is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Eternacore.  If not, see <http://www.gnu.org/licenses/>.
+use std::{sync::Arc, time::Duration};
+
+use eternacore::commands::framework::{
+    File, Message, Modal, Origin, Recorded, Recorder, Response, Sent,
+};
+
+#[tokio::test]
+async fn sent_messages_can_be_edited_and_deleted() {
+    let recorder = Arc::new(Recorder::new());
+    let res = Response::from_sink(recorder.clone(), Origin::InteractionCommand);
+
+    let original = res.send_message("Working...").await.unwrap();
+    let followup = res.send_message("Almost there").await.unwrap();
+    original.edit("Done.").await.unwrap();
+    followup.delete().await.unwrap();
+
+    assert_eq!(original.get_sent(), Sent::Original);
+    assert_eq!(recorder.contents(), ["Working...", "Almost there", "Done."]);
+    assert!(matches!(
+        recorder.recorded().last(),
+        Some(Recorded::Delete(sent)) if *sent == followup.get_sent()
+    ));
+}
+
+#[tokio::test]
+async fn ephemeral_messages_stay_private_after_a_public_deferral() {
+    let recorder = Arc::new(Recorder::new());
+    let res = Response::from_sink(recorder.clone(), Origin::InteractionCommand);
+
+    res.defer(false).await.unwrap();
+    res.send_ephemeral_message("secret", true).await.unwrap();
+
+    let recorded = recorder.recorded();
+    assert!(matches!(
+        recorded.as_slice(),
+        [
+            Recorded::Deferred { ephemeral: false },
+            Recorded::Delete(Sent::Original),
+            Recorded::FollowUp { message, ephemeral: true },
+        ] if message.get_content() == "secret"
+    ));
+}
+
+#[tokio::test]
+async fn messages_fill_in_a_deferral_of_the_same_visibility() {
+    let recorder = Arc::new(Recorder::new());
+    let res = Response::from_sink(recorder.clone(), Origin::InteractionCommand);
+
+    res.defer(true).await.unwrap();
+    res.send_message("Done.").await.unwrap();
+
+    assert!(matches!(
+        recorder.recorded().as_slice(),
+        [
+            Recorded::Deferred { ephemeral: true },
+            Recorded::Edit {
+                sent: Sent::Original,
+                ..
+            },
+        ]
+    ));
+}
+
+#[tokio::test]
+async fn files_fill_in_a_deferral_as_a_follow_up() {
+    let recorder = Arc::new(Recorder::new());
+    let res = Response::from_sink(recorder.clone(), Origin::InteractionCommand);
+
+    res.defer(true).await.unwrap();
+    res.send_ephemeral_message(
+        Message::new("Report").file(File::from_bytes("...", "report.txt")),
+        true,
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(
+        recorder.recorded().as_slice(),
+        [
+            Recorded::Deferred { ephemeral: true },
+            Recorded::FollowUp { message, ephemeral: true },
+        ] if message.get_files().len() == 1
+    ));
+}
+
+#[tokio::test]
+async fn aborted_deferrals_never_happen() {
+    let recorder = Arc::new(Recorder::new());
+    let res = Response::from_sink(recorder.clone(), Origin::InteractionCommand);
+
+    res.defer_after(Duration::from_millis(10), false).abort();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert!(recorder.recorded().is_empty());
+    assert!(!res.is_acknowledged().await);
+}
+
+#[tokio::test]
+async fn modals_cant_be_opened_after_deferring() {
+    let recorder = Arc::new(Recorder::new());
+    let res = Response::from_sink(recorder.clone(), Origin::InteractionCommand);
+
+    res.defer(false).await.unwrap();
+
+    assert!(res
+        .open_modal(Modal::new("report", "Report"))
+        .await
+        .is_err());
+    assert!(matches!(
+        recorder.recorded().as_slice(),
+        [Recorded::Deferred { ephemeral: false }]
+    ));
+}